@@ -1,23 +1,36 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
+// Read-side helpers such as the stat averages are only used by the service
+#[allow(dead_code)]
 mod state;
 
+use std::cmp::Ordering;
+
 use linera_sdk::{
     abi::WithContractAbi,
     views::{RootView, View},
     Contract, ContractRuntime,
-    linera_base_types::{AccountOwner, ChainId, CryptoHash, Timestamp},
+    linera_base_types::{AccountOwner, ChainId, CryptoHash, TimeDelta, Timestamp},
 };
 use linera_base::crypto::{BcsHashable, CryptoHash as CryptoHashGen};
 use serde::{Deserialize, Serialize};
-use state::{GameRoom, PlayerInfo, PlayerScore, PlayerStats, TypeArenaState};
-use type_arena::{Message, Operation, PlayerResult, RoomStatus, TypeArenaAbi, WORD_BANK};
+use state::{
+    GameRoom, MatchRecord, MatchTicket, NameClaim, PendingJoin, PlayerRank, PlayerStats,
+    QueueEntry, RatingChange, RegisteredName, RoomOrder, TypeArenaState,
+};
+use type_arena::{
+    is_reserved_name, is_valid_display_name, normalize_name, InstantiationArgument, JoinStatus,
+    KeystrokeLog, MatchPreferences, Message, Operation, PlayerResult, Profile, Prompt,
+    PromptFilter, PromptTags, Rating, Replay, RoomListing, RoomSettings, RoomStatus, TypeArenaAbi,
+    TypeArenaError, TypeArenaParameters, HOST_TIMEOUT_SECS, KICK_COOLDOWN_SECS, MATCH_RATING_SPREAD, MATCH_SPREAD_GROWTH_PER_SEC, MAX_PROMPT_CHARS,
+    MAX_SEED_SECRET_BYTES, MIN_SEED_SECRET_BYTES, WORD_BANK,
+};
 
 /// Wrapper for generating deterministic random hashes
 #[derive(Serialize, Deserialize)]
 struct SeedWrapper(Vec<u8>);
 
-impl<'de> BcsHashable<'de> for SeedWrapper {}
+impl BcsHashable<'_> for SeedWrapper {}
 
 pub struct TypeArenaContract {
     state: TypeArenaState,
@@ -32,8 +45,8 @@ impl WithContractAbi for TypeArenaContract {
 
 impl Contract for TypeArenaContract {
     type Message = Message;
-    type Parameters = ();
-    type InstantiationArgument = ();
+    type Parameters = TypeArenaParameters;
+    type InstantiationArgument = InstantiationArgument;
     type EventValue = ();
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
@@ -43,53 +56,296 @@ impl Contract for TypeArenaContract {
         TypeArenaContract { state, runtime }
     }
 
-    async fn instantiate(&mut self, _argument: ()) {
-        // Nothing to initialize
+    async fn instantiate(&mut self, argument: InstantiationArgument) {
+        // Validate the deployment's rules up front
+        let parameters = self.runtime.application_parameters();
+        assert!(parameters.max_players >= 2, "max_players must allow at least 2 players");
+        assert!(parameters.max_rounds >= 1, "max_rounds must allow at least 1 round");
+        assert!(
+            (10..=600).contains(&parameters.default_round_time_limit_secs),
+            "Invalid default round time limit (10-600s)"
+        );
+        assert!(parameters.countdown_secs <= 60, "Countdown must be at most 60s");
+        // A game must outlive its longest possible round and the wait before a host claim
+        assert!(
+            parameters.room_idle_expiry_secs >= 600 + parameters.countdown_secs as u64 + HOST_TIMEOUT_SECS,
+            "Room idle expiry must cover the longest round plus the host timeout"
+        );
+        assert!(parameters.lobby_max_age_secs >= 600, "Lobbies must stay open for at least 600s");
+        
+        // Without an admin the library could never be changed, so default to the creator
+        let admin = argument.admin
+            .or_else(|| self.runtime.authenticated_signer())
+            .expect("An admin is required when the application is created without a signer");
+        self.state.admins.insert(&admin).expect("Failed to add admin");
+        
+        // Seed the prompt library on the creator chain
+        let texts = if argument.prompts.is_empty() {
+            WORD_BANK.iter().map(|text| text.to_string()).collect()
+        } else {
+            argument.prompts
+        };
+        for (id, text) in (0..).zip(texts) {
+            assert!(Self::is_valid_prompt(&text), "Invalid prompt in instantiation argument");
+            let prompt = Prompt {
+                id,
+                tags: PromptTags::default_for(&text),
+                text,
+                retired: false,
+            };
+            self.state.prompts.insert(&id, prompt).expect("Failed to add prompt");
+            self.state.next_prompt_id.set(id + 1);
+        }
     }
 
     async fn execute_operation(&mut self, operation: Operation) -> Self::Response {
         match operation {
-            Operation::CreateRoom { room_id, max_players, rounds } => {
-                self.create_room(room_id, max_players, rounds).await;
+            Operation::CreateRoom { room_id, settings, seed_commitment } => {
+                self.create_room(room_id, settings, seed_commitment).await
+            }
+            Operation::JoinRoom { room_id, host_chain_id, seed_commitment, passphrase } => {
+                self.join_room(room_id, host_chain_id, seed_commitment, passphrase).await
             }
-            Operation::JoinRoom { room_id, host_chain_id, player_name } => {
-                self.join_room(room_id, host_chain_id, player_name).await;
+            Operation::CommitSeed { room_id, host_chain_id, seed_commitment } => {
+                self.commit_seed(room_id, host_chain_id, seed_commitment).await
             }
-            Operation::StartGame { room_id } => {
-                self.start_game(room_id).await;
+            Operation::RevealSeed { room_id, host_chain_id, secret } => {
+                self.reveal_seed(room_id, host_chain_id, secret).await
             }
-            Operation::SubmitResult { room_id, host_chain_id, wpm, accuracy, time_ms } => {
-                self.submit_result(room_id, host_chain_id, wpm, accuracy, time_ms).await;
+            Operation::SetReady { room_id, host_chain_id } => {
+                self.set_ready(room_id, host_chain_id, true).await
+            }
+            Operation::SetNotReady { room_id, host_chain_id } => {
+                self.set_ready(room_id, host_chain_id, false).await
+            }
+            Operation::StartGame { room_id, secret } => {
+                self.start_game(room_id, secret).await
+            }
+            Operation::SubmitResult { room_id, host_chain_id, keystrokes } => {
+                self.submit_result(room_id, host_chain_id, keystrokes).await
             }
             Operation::LeaveRoom { room_id, host_chain_id } => {
-                self.leave_room(room_id, host_chain_id).await;
+                self.leave_room(room_id, host_chain_id)
+            }
+            Operation::CloseRound { room_id, host_chain_id } => {
+                self.close_round(room_id, host_chain_id).await
+            }
+            Operation::TransferHost { room_id, new_host } => {
+                self.transfer_host(room_id, new_host).await
+            }
+            Operation::KickPlayer { room_id, player } => {
+                let until = self.runtime.system_time().saturating_add(TimeDelta::from_secs(KICK_COOLDOWN_SECS));
+                self.remove_player(room_id, player, Some(until)).await
+            }
+            Operation::BanPlayer { room_id, player } => {
+                self.remove_player(room_id, player, None).await
+            }
+            Operation::ClaimHost { room_id } => {
+                self.claim_host(room_id).await
+            }
+            Operation::AddPrompt { text, tags } => {
+                self.add_prompt(text, tags).await
+            }
+            Operation::RetirePrompt { prompt_id } => {
+                self.retire_prompt(prompt_id).await
+            }
+            Operation::TagPrompt { prompt_id, tags } => {
+                self.tag_prompt(prompt_id, tags).await
+            }
+            Operation::AddAdmin { owner } => {
+                self.add_admin(owner).await
+            }
+            Operation::RemoveAdmin { owner } => {
+                self.remove_admin(owner).await
+            }
+            Operation::EnqueueMatch { preferences, seed_commitment } => {
+                self.enqueue_match(preferences, seed_commitment).await
+            }
+            Operation::LeaveQueue => {
+                self.leave_queue().await
+            }
+            Operation::SetProfile { profile } => {
+                self.set_profile(profile).await
+            }
+            Operation::ClaimName { name } => {
+                self.claim_name(name).await
+            }
+            Operation::ReleaseName => {
+                self.release_name().await
+            }
+            Operation::TransferName { new_owner, new_owner_chain_id } => {
+                self.transfer_name(new_owner, new_owner_chain_id).await
+            }
+            Operation::CleanupRooms { limit } => {
+                self.cleanup_rooms(limit).await;
+                Ok(())
             }
         }
     }
 
     async fn execute_message(&mut self, message: Message) {
-        match message {
-            Message::PlayerJoined { room_id, player, player_chain_id, player_name } => {
-                self.handle_player_joined(room_id, player, player_chain_id, player_name).await;
+        // Requests handled by the host answer failures with a `Rejected` message
+        let result = match message {
+            Message::PlayerJoined { room_id, player, player_chain_id, profile, seed_commitment, passphrase } => {
+                // Joins always get an explicit answer so the player's chain can track them
+                let reply = match self.handle_player_joined(room_id, player, player_chain_id, profile, seed_commitment, passphrase).await {
+                    Ok(()) => Message::JoinAccepted { room_id },
+                    Err(reason) => Message::JoinRejected { room_id, reason },
+                };
+                self.runtime.prepare_message(reply).send_to(player_chain_id);
+                Ok(())
+            }
+            Message::JoinAccepted { room_id } => {
+                self.handle_join_answered(room_id, JoinStatus::Accepted, None).await;
+                Ok(())
+            }
+            Message::JoinRejected { room_id, reason } => {
+                self.handle_join_answered(room_id, JoinStatus::Rejected, Some(reason)).await;
+                Ok(())
+            }
+            Message::SeedCommitted { room_id, player, seed_commitment } => {
+                self.process_seed_commitment(room_id, player, seed_commitment).await
+                    .map_err(|error| (room_id, error))
+            }
+            Message::SeedRevealed { room_id, player, secret } => {
+                self.process_seed_reveal(room_id, player, secret).await
+                    .map_err(|error| (room_id, error))
+            }
+            Message::ReadyChanged { room_id, player, ready } => {
+                self.process_ready_change(room_id, player, ready).await
+                    .map_err(|error| (room_id, error))
             }
             Message::GameStarted { room_id, prompt, round, start_time } => {
                 self.handle_game_started(room_id, prompt, round, start_time).await;
+                Ok(())
             }
-            Message::ResultSubmitted { room_id, player, wpm, accuracy, time_ms } => {
-                self.handle_result_submitted(room_id, player, wpm, accuracy, time_ms).await;
+            Message::ResultSubmitted { room_id, player, keystrokes } => {
+                self.handle_result_submitted(room_id, player, keystrokes).await
+                    .map_err(|error| (room_id, error))
             }
-            Message::RoundEnded { room_id, round, results, next_prompt } => {
-                self.handle_round_ended(room_id, round, results, next_prompt).await;
+            Message::RoundEnded { room_id, round, results, next_prompt, start_time } => {
+                self.handle_round_ended(room_id, round, results, next_prompt, start_time).await;
+                Ok(())
             }
             Message::GameEnded { room_id, final_standings, winner } => {
                 self.handle_game_ended(room_id, final_standings, winner).await;
+                Ok(())
             }
             Message::PlayerLeft { room_id, player } => {
-                self.handle_player_left(room_id, player).await;
+                self.handle_player_left(room_id, player).await
+                    .map_err(|error| (room_id, error))
+            }
+            Message::PlayerRemoved { room_id, player, until } => {
+                self.handle_player_removed(room_id, player, until).await;
+                Ok(())
+            }
+            Message::CloseRound { room_id } => {
+                self.close_expired_round(room_id).await
+                    .map_err(|error| (room_id, error))
+            }
+            Message::HostTransferred { room_id, state_bytes } => {
+                // The new host installs the authoritative copy the same way as a sync,
+                // and from now on draws prompts from its own copy of the library
+                self.handle_sync_state(room_id, state_bytes).await;
+                self.subscribe_to_prompts();
+                Ok(())
+            }
+            Message::HostChanged { room_id, host, host_chain_id, host_epoch } => {
+                self.handle_host_changed(room_id, host, host_chain_id, host_epoch).await;
+                Ok(())
             }
             Message::SyncState { room_id, state_bytes } => {
                 self.handle_sync_state(room_id, state_bytes).await;
+                Ok(())
+            }
+            Message::SubscribePrompts => {
+                self.handle_subscribe_prompts().await;
+                Ok(())
+            }
+            Message::PromptsUpdated { prompts } => {
+                self.handle_prompts_updated(prompts);
+                Ok(())
+            }
+            Message::EnqueueMatch { player, player_chain_id, profile, seed_commitment, preferences } => {
+                // Queue requests carry no room, so failures get their own answer
+                if let Err(reason) = self.handle_enqueue_match(player, player_chain_id, profile, seed_commitment, preferences).await {
+                    self.runtime
+                        .prepare_message(Message::QueueRejected { player, reason })
+                        .send_to(player_chain_id);
+                }
+                Ok(())
+            }
+            Message::LeaveQueue { player } => {
+                self.handle_leave_queue(player);
+                Ok(())
+            }
+            Message::MatchFound { player, room_id, host_chain_id } => {
+                self.handle_match_found(player, room_id, host_chain_id).await;
+                Ok(())
+            }
+            Message::QueueRejected { player, reason } => {
+                self.handle_queue_rejected(player, reason).await;
+                Ok(())
+            }
+            Message::MatchFinished { room_id, final_standings, winner } => {
+                self.handle_match_finished(room_id, final_standings, winner).await;
+                Ok(())
+            }
+            Message::ProfileUpdated { owner, profile } => {
+                self.handle_profile_updated(owner, profile).await;
+                Ok(())
+            }
+            Message::ClaimName { owner, owner_chain_id, name } => {
+                // Claims carry no room, so failures get their own answer
+                if let Err(reason) = self.handle_claim_name(owner, owner_chain_id, name).await {
+                    self.runtime
+                        .prepare_message(Message::NameRejected { owner, reason })
+                        .send_to(owner_chain_id);
+                }
+                Ok(())
+            }
+            Message::ReleaseName { owner } => {
+                self.handle_release_name(owner).await;
+                Ok(())
+            }
+            Message::TransferName { owner, new_owner, new_owner_chain_id } => {
+                if let Err(reason) = self.handle_transfer_name(owner, new_owner, new_owner_chain_id).await {
+                    if let Some(origin) = self.runtime.message_origin_chain_id() {
+                        self.runtime
+                            .prepare_message(Message::NameRejected { owner, reason })
+                            .send_to(origin);
+                    }
+                }
+                Ok(())
+            }
+            Message::NameClaimed { owner, name } => {
+                self.handle_name_claimed(owner, name).await;
+                Ok(())
+            }
+            Message::NameReleased { owner } => {
+                self.handle_name_released(owner).await;
+                Ok(())
+            }
+            Message::NameRejected { owner, reason } => {
+                self.handle_name_rejected(owner, reason).await;
+                Ok(())
+            }
+            Message::RegisterRoom { listing } => {
+                self.handle_register_room(listing).await;
+                Ok(())
+            }
+            Message::DeregisterRoom { room_id, host_epoch } => {
+                self.handle_deregister_room(room_id, host_epoch).await;
+                Ok(())
+            }
+            Message::Rejected { room_id, error } => {
+                self.handle_rejected(room_id, error);
+                Ok(())
             }
+        };
+        
+        if let Err((room_id, error)) = result {
+            self.reject(room_id, error);
         }
     }
 
@@ -100,75 +356,187 @@ impl Contract for TypeArenaContract {
 
 impl TypeArenaContract {
     /// Create a new game room (executed on host chain)
-    async fn create_room(&mut self, room_id: CryptoHash, max_players: u8, rounds: u8) {
-        assert!(max_players >= 2 && max_players <= 8, "Invalid player count (2-8)");
-        assert!(rounds >= 1 && rounds <= 10, "Invalid round count (1-10)");
+    async fn create_room(&mut self, room_id: CryptoHash, settings: RoomSettings, seed_commitment: CryptoHash) -> Result<(), TypeArenaError> {
+        let RoomSettings { max_players, rounds, round_time_limit_secs, prompt_filter, access, auto_start } = settings;
+        let prompt_filter = prompt_filter.unwrap_or_default();
+        let access = access.unwrap_or_default();
+        let parameters = self.runtime.application_parameters();
+        if !(2..=parameters.max_players).contains(&max_players) {
+            return Err(TypeArenaError::InvalidPlayerCount);
+        }
+        if !(1..=parameters.max_rounds).contains(&rounds) {
+            return Err(TypeArenaError::InvalidRoundCount);
+        }
+        let round_time_limit_secs = round_time_limit_secs.unwrap_or(parameters.default_round_time_limit_secs);
+        if !(10..=600).contains(&round_time_limit_secs) {
+            return Err(TypeArenaError::InvalidRoundTimeLimit);
+        }
+        if !access.is_valid() {
+            return Err(TypeArenaError::InvalidRoomAccess);
+        }
         
-        let existing = self.state.rooms.get(&room_id).await.expect("Failed to get room");
-        assert!(existing.is_none(), "Room already exists");
+        if self.state.rooms.contains_key(&room_id).await.expect("Failed to get room") {
+            return Err(TypeArenaError::RoomAlreadyExists);
+        }
         
-        let host = self.runtime.authenticated_signer().expect("No authenticated signer");
+        let host = self.signer()?;
         let host_chain_id = self.runtime.chain_id();
         let created_at = self.runtime.system_time();
         
-        // Generate a display name from the owner
-        let host_name = format!("Player_{}", &host.to_string()[..8]);
+        let host_name = self.profile_of(&host).await.display_name;
         
-        let room = GameRoom::new(
+        let mut room = GameRoom::new(
             room_id,
             host,
             host_chain_id,
             host_name,
+            seed_commitment,
             max_players,
             rounds,
+            round_time_limit_secs,
+            prompt_filter,
             created_at,
         );
+        room.access = access;
+        room.auto_start = auto_start.unwrap_or(false);
+        
+        self.publish_listing(&room).await;
+        self.save_room(room);
         
-        self.state.rooms.insert(&room_id, room).expect("Failed to insert room");
+        // Hosts draw prompts from their local copy of the library
+        self.subscribe_to_prompts();
+        Ok(())
     }
     
     /// Join a room (sends message to host chain)
-    async fn join_room(&mut self, room_id: CryptoHash, host_chain_id: ChainId, player_name: String) {
-        let player = self.runtime.authenticated_signer().expect("No authenticated signer");
+    async fn join_room(&mut self, room_id: CryptoHash, host_chain_id: ChainId, seed_commitment: CryptoHash, passphrase: Option<String>) -> Result<(), TypeArenaError> {
+        let player = self.signer()?;
         let player_chain_id = self.runtime.chain_id();
+        let profile = self.profile_of(&player).await;
+        
+        // Track the request until the host answers
+        let pending = PendingJoin {
+            room_id,
+            host_chain_id,
+            status: JoinStatus::Pending,
+            reason: None,
+            requested_at: self.runtime.system_time(),
+        };
+        self.state.pending_joins.insert(&room_id, pending).expect("Failed to record join");
         
         // Send join request to host chain
         let message = Message::PlayerJoined {
             room_id,
             player,
             player_chain_id,
-            player_name,
+            profile,
+            seed_commitment,
+            passphrase,
         };
         
         self.runtime
             .prepare_message(message)
             .send_to(host_chain_id);
+        Ok(())
+    }
+    
+    /// Commit to a fresh seed secret (sends to host if not on host chain)
+    async fn commit_seed(&mut self, room_id: CryptoHash, host_chain_id: ChainId, seed_commitment: CryptoHash) -> Result<(), TypeArenaError> {
+        let player = self.signer()?;
+        
+        if self.runtime.chain_id() == host_chain_id {
+            self.process_seed_commitment(room_id, player, seed_commitment).await
+        } else {
+            self.runtime
+                .prepare_message(Message::SeedCommitted { room_id, player, seed_commitment })
+                .send_to(host_chain_id);
+            Ok(())
+        }
+    }
+    
+    /// Reveal this player's seed secret (sends to host if not on host chain)
+    async fn reveal_seed(&mut self, room_id: CryptoHash, host_chain_id: ChainId, secret: Vec<u8>) -> Result<(), TypeArenaError> {
+        let player = self.signer()?;
+        if !(MIN_SEED_SECRET_BYTES..=MAX_SEED_SECRET_BYTES).contains(&secret.len()) {
+            return Err(TypeArenaError::InvalidSeedSecret);
+        }
+        
+        if self.runtime.chain_id() == host_chain_id {
+            self.process_seed_reveal(room_id, player, secret).await
+        } else {
+            self.runtime
+                .prepare_message(Message::SeedRevealed { room_id, player, secret })
+                .send_to(host_chain_id);
+            Ok(())
+        }
+    }
+    
+    /// Mark this player ready or not ready (sends to host if not on host chain)
+    async fn set_ready(&mut self, room_id: CryptoHash, host_chain_id: ChainId, ready: bool) -> Result<(), TypeArenaError> {
+        let player = self.signer()?;
+        
+        if self.runtime.chain_id() == host_chain_id {
+            self.process_ready_change(room_id, player, ready).await
+        } else {
+            self.runtime
+                .prepare_message(Message::ReadyChanged { room_id, player, ready })
+                .send_to(host_chain_id);
+            Ok(())
+        }
     }
     
     /// Start the game (host only)
-    async fn start_game(&mut self, room_id: CryptoHash) {
-        let mut room = self.state.rooms.get(&room_id).await
-            .expect("Failed to get room")
-            .expect("Room not found");
+    async fn start_game(&mut self, room_id: CryptoHash, secret: Vec<u8>) -> Result<(), TypeArenaError> {
+        let mut room = self.load_room(&room_id).await?;
+        
+        let caller = self.signer()?;
+        if room.host != caller {
+            return Err(TypeArenaError::NotHost);
+        }
+        if room.status != RoomStatus::Lobby {
+            return Err(TypeArenaError::GameAlreadyStarted);
+        }
+        
+        // Starting counts as the host's own reveal; the roster locked when the host
+        // marked ready, so nobody can join or be kicked to change the combined seed
+        room.reveal_seed(&caller, secret)?;
+        self.begin_game(&mut room).await?;
+        
+        self.save_room(room);
+        Ok(())
+    }
+    
+    /// Start the first round once every player is ready and has revealed their seed
+    async fn begin_game(&mut self, room: &mut GameRoom) -> Result<(), TypeArenaError> {
+        if room.players.len() < 2 {
+            return Err(TypeArenaError::NotEnoughPlayers);
+        }
+        if !room.all_ready() {
+            return Err(TypeArenaError::NotAllReady);
+        }
         
-        let caller = self.runtime.authenticated_signer().expect("No authenticated signer");
-        assert!(room.host == caller, "Only host can start game");
-        assert!(room.status == RoomStatus::Lobby, "Game already started");
-        assert!(room.players.len() >= 2, "Need at least 2 players");
+        // Every player's secret feeds the prompt seed, so no one chain picks the prompts
+        if !room.all_seeds_revealed() {
+            return Err(TypeArenaError::SeedsNotRevealed);
+        }
+        room.prompt_seed = Some(Self::combine_seeds(room));
+        room.round_entropy = None;
         
         // Start first round
         room.status = RoomStatus::InProgress;
         room.current_round = 1;
         room.round_results.clear();
+        room.round_history.clear();
         
-        let prompt = self.get_random_prompt();
-        let start_time = self.runtime.system_time();
+        room.used_prompt_ids.clear();
+        let prompt = self.get_random_prompt(room).await?;
+        let start_time = self.round_start_after_countdown();
         room.current_prompt = Some(prompt.clone());
         room.round_start_time = Some(start_time);
         
         // Broadcast game start to all players
         let message = Message::GameStarted {
-            room_id,
+            room_id: room.room_id,
             prompt: prompt.clone(),
             round: 1,
             start_time,
@@ -182,35 +550,51 @@ impl TypeArenaContract {
             }
         }
         
-        self.state.rooms.insert(&room_id, room).expect("Failed to update room");
+        // Started games are no longer joinable
+        self.publish_listing(room).await;
+        Ok(())
+    }
+    
+    /// Begin an auto-start room if the last missing ready mark or seed just arrived
+    async fn maybe_auto_start(&mut self, room: &mut GameRoom) {
+        if !room.auto_start || room.status != RoomStatus::Lobby {
+            return;
+        }
+        // Only keep the changes if the game actually started
+        let mut started = room.clone();
+        if self.begin_game(&mut started).await.is_ok() {
+            *room = started;
+        }
     }
     
     /// Submit typing result (sends to host if not on host chain)
-    async fn submit_result(&mut self, room_id: CryptoHash, host_chain_id: ChainId, wpm: u32, accuracy: u32, time_ms: u64) {
-        let player = self.runtime.authenticated_signer().expect("No authenticated signer");
+    async fn submit_result(&mut self, room_id: CryptoHash, host_chain_id: ChainId, keystrokes: Vec<u8>) -> Result<(), TypeArenaError> {
+        let player = self.signer()?;
+        
+        // Reject malformed logs before they reach the host
+        KeystrokeLog::decode(&keystrokes).and_then(|log| log.replay())?;
         
         if self.runtime.chain_id() == host_chain_id {
             // We're on the host chain, process directly
-            self.process_result(room_id, player, wpm, accuracy, time_ms).await;
+            self.process_result(room_id, player, keystrokes).await
         } else {
             // Send result to host chain
             let message = Message::ResultSubmitted {
                 room_id,
                 player,
-                wpm,
-                accuracy,
-                time_ms,
+                keystrokes,
             };
             
             self.runtime
                 .prepare_message(message)
                 .send_to(host_chain_id);
+            Ok(())
         }
     }
     
     /// Leave a room
-    async fn leave_room(&mut self, room_id: CryptoHash, host_chain_id: ChainId) {
-        let player = self.runtime.authenticated_signer().expect("No authenticated signer");
+    fn leave_room(&mut self, room_id: CryptoHash, host_chain_id: ChainId) -> Result<(), TypeArenaError> {
+        let player = self.signer()?;
         
         let message = Message::PlayerLeft {
             room_id,
@@ -220,159 +604,1116 @@ impl TypeArenaContract {
         self.runtime
             .prepare_message(message)
             .send_to(host_chain_id);
+        Ok(())
     }
     
-    // === Message Handlers (executed on receiving chain) ===
+    /// Close an expired round (sends to host if not on host chain)
+    async fn close_round(&mut self, room_id: CryptoHash, host_chain_id: ChainId) -> Result<(), TypeArenaError> {
+        if self.runtime.chain_id() == host_chain_id {
+            self.close_expired_round(room_id).await
+        } else {
+            self.runtime
+                .prepare_message(Message::CloseRound { room_id })
+                .send_to(host_chain_id);
+            Ok(())
+        }
+    }
     
-    async fn handle_player_joined(&mut self, room_id: CryptoHash, player: AccountOwner, player_chain_id: ChainId, player_name: String) {
-        let mut room = match self.state.rooms.get(&room_id).await.expect("Failed to get room") {
-            Some(r) => r,
-            None => return, // Room doesn't exist on this chain
-        };
+    /// Hand the room over to another player (host only, executed on host chain)
+    async fn transfer_host(&mut self, room_id: CryptoHash, new_host: AccountOwner) -> Result<(), TypeArenaError> {
+        let mut room = self.load_room(&room_id).await?;
         
-        // Validate join
-        if room.is_full() || room.status != RoomStatus::Lobby || room.has_player(&player) {
-            return;
+        let caller = self.signer()?;
+        if room.host != caller {
+            return Err(TypeArenaError::NotHost);
+        }
+        if room.host_chain_id != self.runtime.chain_id() {
+            return Err(TypeArenaError::NotHostChain);
+        }
+        if new_host == caller {
+            return Err(TypeArenaError::AlreadyHost);
+        }
+        if !room.has_player(&new_host) {
+            return Err(TypeArenaError::NotInRoom);
         }
         
-        // Add player
-        let player_info = PlayerInfo {
-            owner: player,
-            chain_id: player_chain_id,
-            display_name: player_name,
-            is_ready: true,
-        };
-        room.players.push(player_info);
-        room.game_scores.push(PlayerScore {
-            player,
-            total_score: 0,
-            rounds_completed: 0,
-        });
-        
-        // Sync state to all players
-        self.broadcast_state_sync(&room).await;
+        self.hand_over_host(&mut room, new_host);
+        self.publish_listing(&room).await;
         
-        self.state.rooms.insert(&room_id, room).expect("Failed to update room");
+        self.save_room(room);
+        Ok(())
     }
     
-    async fn handle_game_started(&mut self, room_id: CryptoHash, prompt: String, round: u8, start_time: Timestamp) {
-        // Create or update room on player's chain
-        if let Some(mut room) = self.state.rooms.get(&room_id).await.expect("Failed to get room") {
-            room.status = RoomStatus::InProgress;
-            room.current_round = round;
-            room.current_prompt = Some(prompt);
-            room.round_start_time = Some(start_time);
-            room.round_results.clear();
-            self.state.rooms.insert(&room_id, room).expect("Failed to update room");
+    /// Kick (`until` set) or ban a player from the room (host only, on the host chain)
+    async fn remove_player(&mut self, room_id: CryptoHash, player: AccountOwner, until: Option<Timestamp>) -> Result<(), TypeArenaError> {
+        let mut room = self.load_host_room(&room_id).await?;
+        
+        let caller = self.signer()?;
+        if room.host != caller {
+            return Err(TypeArenaError::NotHost);
         }
+        if player == caller {
+            return Err(TypeArenaError::CannotRemoveHost);
+        }
+        // Kicking after seeing the revealed secrets would let the host reshuffle the seed
+        if room.status == RoomStatus::Lobby && room.roster_locked {
+            return Err(TypeArenaError::RosterLocked);
+        }
+        let player_chain_id = room.get_player_chain(&player).ok_or(TypeArenaError::NotInRoom)?;
+        
+        room.remove_player(&player);
+        room.ban(player, until);
+        
+        // The removed player's chain stops getting syncs, so tell it directly
+        if player_chain_id != self.runtime.chain_id() {
+            self.runtime
+                .prepare_message(Message::PlayerRemoved { room_id, player, until })
+                .send_to(player_chain_id);
+        }
+        
+        // The round may have been waiting only on the removed player
+        if room.status == RoomStatus::InProgress && room.all_results_submitted() {
+            self.end_round(&mut room).await;
+        } else {
+            self.maybe_auto_start(&mut room).await;
+        }
+        
+        self.broadcast_state_sync(&room).await;
+        self.publish_listing(&room).await;
+        
+        self.save_room(room);
+        Ok(())
     }
     
-    async fn handle_result_submitted(&mut self, room_id: CryptoHash, player: AccountOwner, wpm: u32, accuracy: u32, time_ms: u64) {
-        // Only process on host chain
-        self.process_result(room_id, player, wpm, accuracy, time_ms).await;
-    }
-    
-    async fn process_result(&mut self, room_id: CryptoHash, player: AccountOwner, wpm: u32, accuracy: u32, time_ms: u64) {
-        let mut room = match self.state.rooms.get(&room_id).await.expect("Failed to get room") {
-            Some(r) => r,
-            None => return,
-        };
+    /// Take over a room whose host chain went silent (executed on the claiming player's chain)
+    async fn claim_host(&mut self, room_id: CryptoHash) -> Result<(), TypeArenaError> {
+        let mut room = self.load_room(&room_id).await?;
         
+        let caller = self.signer()?;
+        let chain_id = self.runtime.chain_id();
+        let now = self.runtime.system_time();
+        if room.get_player_chain(&caller) != Some(chain_id) {
+            return Err(TypeArenaError::NotInRoom);
+        }
+        if room.host_chain_id == chain_id {
+            return Err(TypeArenaError::AlreadyHost);
+        }
+        // Only games can stall on a dead host; players of a quiet lobby simply leave
         if room.status != RoomStatus::InProgress {
-            return;
+            return Err(TypeArenaError::GameNotInProgress);
         }
-        
-        // Check if player already submitted
-        if room.round_results.iter().any(|r| r.player == player) {
-            return;
+        if !room.host_timed_out(now) {
+            return Err(TypeArenaError::HostStillActive);
         }
         
-        // Get player name
-        let player_name = room.players.iter()
-            .find(|p| p.owner == player)
-            .map(|p| p.display_name.clone())
-            .unwrap_or_else(|| "Unknown".to_string());
+        // Results sent to the old host are lost, so replay the interrupted round. Everyone
+        // has seen its prompt by now, so the replay gets a fresh one unless none is left.
+        let prompt = match self.get_random_prompt(&mut room).await {
+            Ok(prompt) => prompt,
+            Err(TypeArenaError::NoMatchingPrompts) => room.current_prompt.clone().unwrap_or_default(),
+            Err(error) => return Err(error),
+        };
         
-        let score = PlayerResult::calculate_score(wpm, accuracy);
+        let previous_host_chain_id = room.host_chain_id;
+        room.host = caller;
+        room.host_chain_id = chain_id;
+        room.host_epoch += 1;
+        room.last_host_activity = now;
         
-        let result = PlayerResult {
-            player,
-            player_name,
-            wpm,
-            accuracy,
-            time_ms,
-            score,
-        };
+        // Players only take round messages from the chain they know as host, so
+        // the announcement has to reach them before the replayed round
+        self.announce_host(&room, Some(previous_host_chain_id));
         
-        room.round_results.push(result);
+        let start_time = self.round_start_after_countdown();
+        room.round_results.clear();
+        room.current_prompt = Some(prompt.clone());
+        room.round_start_time = Some(start_time);
         
-        // Update player's game score
-        if let Some(game_score) = room.game_scores.iter_mut().find(|s| s.player == player) {
-            game_score.total_score += score;
-            game_score.rounds_completed += 1;
+        let message = Message::GameStarted {
+            room_id,
+            prompt,
+            round: room.current_round,
+            start_time,
+        };
+        
+        for player_info in &room.players {
+            if player_info.chain_id != chain_id {
+                self.runtime
+                    .prepare_message(message.clone())
+                    .send_to(player_info.chain_id);
+            }
         }
         
-        // Update player stats
-        let mut stats = self.state.player_stats.get(&player).await
-            .expect("Failed to get stats")
-            .unwrap_or_default();
-        stats.update_with_result(wpm, accuracy);
-        self.state.player_stats.insert(&player, stats).expect("Failed to update stats");
+        self.subscribe_to_prompts();
         
-        // Check if all players submitted
-        if room.all_results_submitted() {
-            self.end_round(&mut room).await;
+        self.save_room(room);
+        Ok(())
+    }
+    
+    // === Prompt Library (managed on the creator chain) ===
+    
+    async fn add_prompt(&mut self, text: String, tags: PromptTags) -> Result<(), TypeArenaError> {
+        self.ensure_admin().await?;
+        if !Self::is_valid_prompt(&text) {
+            return Err(TypeArenaError::InvalidPrompt);
         }
         
-        self.state.rooms.insert(&room_id, room).expect("Failed to update room");
+        let id = *self.state.next_prompt_id.get();
+        self.state.next_prompt_id.set(id + 1);
+        let prompt = Prompt {
+            id,
+            text,
+            tags,
+            retired: false,
+        };
+        self.save_prompt(prompt).await;
+        Ok(())
     }
     
-    async fn end_round(&mut self, room: &mut GameRoom) {
-        let results = room.round_results.clone();
+    async fn retire_prompt(&mut self, prompt_id: u32) -> Result<(), TypeArenaError> {
+        self.ensure_admin().await?;
+        let mut prompt = self.load_prompt(prompt_id).await?;
+        prompt.retired = true;
+        self.save_prompt(prompt).await;
+        Ok(())
+    }
+    
+    async fn tag_prompt(&mut self, prompt_id: u32, tags: PromptTags) -> Result<(), TypeArenaError> {
+        self.ensure_admin().await?;
+        let mut prompt = self.load_prompt(prompt_id).await?;
+        prompt.tags = tags;
+        self.save_prompt(prompt).await;
+        Ok(())
+    }
+    
+    async fn add_admin(&mut self, owner: AccountOwner) -> Result<(), TypeArenaError> {
+        self.ensure_admin().await?;
+        self.state.admins.insert(&owner).expect("Failed to add admin");
+        Ok(())
+    }
+    
+    async fn remove_admin(&mut self, owner: AccountOwner) -> Result<(), TypeArenaError> {
+        self.ensure_admin().await?;
+        let admins = self.state.admins.indices().await.expect("Failed to get admins");
+        if admins == [owner] {
+            return Err(TypeArenaError::LastAdmin);
+        }
+        self.state.admins.remove(&owner).expect("Failed to remove admin");
+        Ok(())
+    }
+    
+    /// Library changes are only accepted from admins on the creator chain
+    async fn ensure_admin(&mut self) -> Result<(), TypeArenaError> {
+        if self.runtime.chain_id() != self.runtime.application_creator_chain_id() {
+            return Err(TypeArenaError::NotLibraryChain);
+        }
+        let caller = self.signer()?;
+        if !self.state.admins.contains(&caller).await.expect("Failed to get admins") {
+            return Err(TypeArenaError::NotAdmin);
+        }
+        Ok(())
+    }
+    
+    async fn load_prompt(&self, prompt_id: u32) -> Result<Prompt, TypeArenaError> {
+        self.state.prompts.get(&prompt_id).await
+            .expect("Failed to get prompt")
+            .ok_or(TypeArenaError::PromptNotFound)
+    }
+    
+    /// Store a prompt and push it to every subscribed chain
+    async fn save_prompt(&mut self, prompt: Prompt) {
+        self.state.prompts.insert(&prompt.id, prompt.clone()).expect("Failed to update prompt");
         
-        if room.current_round >= room.total_rounds {
-            // Game over
-            room.status = RoomStatus::Finished;
-            
-            // Find winner (highest total score)
-            let winner = room.game_scores.iter()
-                .max_by_key(|s| s.total_score)
-                .map(|s| s.player)
-                .expect("No winner found");
-            
-            // Update winner stats
-            if let Some(mut stats) = self.state.player_stats.get(&winner).await.expect("Failed to get stats") {
-                stats.games_won += 1;
-                self.state.player_stats.insert(&winner, stats).expect("Failed to update stats");
-            }
-            
-            // Update all player game counts
-            for player_info in &room.players {
-                if let Some(mut stats) = self.state.player_stats.get(&player_info.owner).await.expect("Failed to get stats") {
-                    stats.games_played += 1;
-                    self.state.player_stats.insert(&player_info.owner, stats).expect("Failed to update stats");
-                }
-            }
-            
-            // Increment total games
-            let total = self.state.total_games.get();
-            self.state.total_games.set(*total + 1);
-            
-            // Broadcast game end
-            let final_standings: Vec<PlayerResult> = room.game_scores.iter().map(|s| {
-                PlayerResult {
-                    player: s.player,
-                    player_name: room.players.iter()
-                        .find(|p| p.owner == s.player)
-                        .map(|p| p.display_name.clone())
-                        .unwrap_or_default(),
-                    wpm: 0,
+        let subscribers = self.state.prompt_subscribers.indices().await.expect("Failed to get subscribers");
+        for chain_id in subscribers {
+            self.runtime
+                .prepare_message(Message::PromptsUpdated { prompts: vec![prompt.clone()] })
+                .send_to(chain_id);
+        }
+    }
+    
+    fn is_valid_prompt(text: &str) -> bool {
+        !text.trim().is_empty() && text.chars().count() <= MAX_PROMPT_CHARS
+    }
+    
+    /// Ask the creator chain for its library once, so this chain can draw prompts locally
+    fn subscribe_to_prompts(&mut self) {
+        let creator_chain_id = self.runtime.application_creator_chain_id();
+        if self.runtime.chain_id() == creator_chain_id || *self.state.prompts_subscribed.get() {
+            return;
+        }
+        self.state.prompts_subscribed.set(true);
+        self.runtime
+            .prepare_message(Message::SubscribePrompts)
+            .send_to(creator_chain_id);
+    }
+    
+    // === Matchmaking (queue kept on the creator chain) ===
+    
+    /// Ask the matchmaker for a room with similarly rated players
+    async fn enqueue_match(&mut self, preferences: MatchPreferences, seed_commitment: CryptoHash) -> Result<(), TypeArenaError> {
+        let player = self.signer()?;
+        let profile = self.profile_of(&player).await;
+        let parameters = self.runtime.application_parameters();
+        if !(2..=parameters.max_players).contains(&preferences.player_count) {
+            return Err(TypeArenaError::InvalidPlayerCount);
+        }
+        if !(1..=parameters.max_rounds).contains(&preferences.rounds) {
+            return Err(TypeArenaError::InvalidRoundCount);
+        }
+        
+        // One request at a time; a matched or rejected ticket is replaced
+        if let Some(ticket) = self.state.match_tickets.get(&player).await.expect("Failed to get ticket") {
+            if ticket.is_pending() {
+                return Err(TypeArenaError::AlreadyQueued);
+            }
+        }
+        
+        // Track the request until the matchmaker forms a room
+        let ticket = MatchTicket {
+            preferences: preferences.clone(),
+            room_id: None,
+            host_chain_id: None,
+            reason: None,
+            requested_at: self.runtime.system_time(),
+        };
+        self.state.match_tickets.insert(&player, ticket).expect("Failed to record ticket");
+        
+        let player_chain_id = self.runtime.chain_id();
+        let matchmaker_chain_id = self.runtime.application_creator_chain_id();
+        if player_chain_id == matchmaker_chain_id {
+            self.handle_enqueue_match(player, player_chain_id, profile, seed_commitment, preferences).await
+        } else {
+            self.runtime
+                .prepare_message(Message::EnqueueMatch {
+                    player,
+                    player_chain_id,
+                    profile,
+                    seed_commitment,
+                    preferences,
+                })
+                .send_to(matchmaker_chain_id);
+            Ok(())
+        }
+    }
+    
+    /// Stop waiting for a match
+    async fn leave_queue(&mut self) -> Result<(), TypeArenaError> {
+        let player = self.signer()?;
+        if !self.state.match_tickets.contains_key(&player).await.expect("Failed to get ticket") {
+            return Err(TypeArenaError::NotQueued);
+        }
+        self.state.match_tickets.remove(&player).expect("Failed to remove ticket");
+        
+        let matchmaker_chain_id = self.runtime.application_creator_chain_id();
+        if self.runtime.chain_id() == matchmaker_chain_id {
+            self.handle_leave_queue(player);
+        } else {
+            self.runtime
+                .prepare_message(Message::LeaveQueue { player })
+                .send_to(matchmaker_chain_id);
+        }
+        Ok(())
+    }
+    
+    /// Queue a player and form a room if enough similarly rated players are waiting
+    async fn handle_enqueue_match(&mut self, player: AccountOwner, player_chain_id: ChainId, profile: Profile, seed_commitment: CryptoHash, preferences: MatchPreferences) -> Result<(), TypeArenaError> {
+        if self.runtime.chain_id() != self.runtime.application_creator_chain_id() {
+            return Err(TypeArenaError::NotMatchmakerChain);
+        }
+        if self.state.match_queue.contains_key(&player).await.expect("Failed to get queue entry") {
+            return Err(TypeArenaError::AlreadyQueued);
+        }
+        
+        let rating = self.state.player_stats.get(&player).await
+            .expect("Failed to get stats")
+            .unwrap_or_default()
+            .rating
+            .rating;
+        self.state.profiles.insert(&player, profile.clone()).expect("Failed to store profile");
+        let entry = QueueEntry {
+            player,
+            player_chain_id,
+            profile,
+            seed_commitment,
+            preferences: preferences.clone(),
+            rating,
+            enqueued_at: self.runtime.system_time(),
+        };
+        self.state.match_queue.insert(&player, entry).expect("Failed to queue player");
+        
+        self.try_form_match(&preferences).await;
+        Ok(())
+    }
+    
+    fn handle_leave_queue(&mut self, player: AccountOwner) {
+        self.state.match_queue.remove(&player).expect("Failed to dequeue player");
+    }
+    
+    /// Group the tightest run of similarly rated players with these preferences.
+    /// The allowed rating gap widens the longer the group's oldest player has waited.
+    async fn try_form_match(&mut self, preferences: &MatchPreferences) {
+        let mut candidates = Vec::new();
+        self.state.match_queue.for_each_index_value(|_player, entry| {
+            if entry.preferences == *preferences {
+                candidates.push(entry.into_owned());
+            }
+            Ok(())
+        }).await.expect("Failed to read match queue");
+        
+        let size = preferences.player_count as usize;
+        if candidates.len() < size {
+            return;
+        }
+        candidates.sort_by(|a, b| a.rating.total_cmp(&b.rating));
+        
+        let now = self.runtime.system_time();
+        let spread = |group: &[QueueEntry]| group[group.len() - 1].rating - group[0].rating;
+        let best = candidates.windows(size)
+            .enumerate()
+            .filter(|(_, group)| {
+                let waited_secs = group.iter()
+                    .map(|entry| now.delta_since(entry.enqueued_at).as_micros() / 1_000_000)
+                    .max()
+                    .unwrap_or_default();
+                spread(group) <= MATCH_RATING_SPREAD + MATCH_SPREAD_GROWTH_PER_SEC * waited_secs as f64
+            })
+            .min_by(|(_, a), (_, b)| spread(a).total_cmp(&spread(b)))
+            .map(|(start, _)| start);
+        
+        if let Some(start) = best {
+            let players = candidates.drain(start..start + size).collect();
+            self.create_matched_room(preferences, players).await;
+        }
+    }
+    
+    /// Build the room for a formed match, hosted by the player who waited longest,
+    /// and hand it to every player chain
+    async fn create_matched_room(&mut self, preferences: &MatchPreferences, mut players: Vec<QueueEntry>) {
+        let match_id = *self.state.next_match_id.get();
+        self.state.next_match_id.set(match_id + 1);
+        let bytes = bcs::to_bytes(&(self.runtime.chain_id(), match_id)).expect("Serialization failed");
+        let room_id = CryptoHashGen::new(&SeedWrapper(bytes));
+        
+        players.sort_by_key(|entry| entry.enqueued_at);
+        let host = &players[0];
+        let prompt_filter = PromptFilter {
+            category: preferences.category,
+            ..PromptFilter::default()
+        };
+        let mut room = GameRoom::new(
+            room_id,
+            host.player,
+            host.player_chain_id,
+            host.profile.display_name.clone(),
+            host.seed_commitment,
+            preferences.player_count,
+            preferences.rounds,
+            self.runtime.application_parameters().default_round_time_limit_secs,
+            prompt_filter,
+            self.runtime.system_time(),
+        );
+        room.matchmade = true;
+        room.auto_start = true;
+        for entry in &players[1..] {
+            room.add_player(entry.player, entry.player_chain_id, entry.profile.display_name.clone(), entry.seed_commitment);
+        }
+        
+        let mut player_chains: Vec<ChainId> = players.iter().map(|entry| entry.player_chain_id).collect();
+        player_chains.sort();
+        player_chains.dedup();
+        self.state.open_matches.insert(&room_id, player_chains.clone()).expect("Failed to record match");
+        
+        // Player chains install the room from the sync; the host chain becomes its authority
+        self.broadcast_state_sync(&room).await;
+        let own_chain_id = self.runtime.chain_id();
+        if player_chains.contains(&own_chain_id) {
+            self.save_room(room.clone());
+        }
+        
+        for entry in &players {
+            self.state.match_queue.remove(&entry.player).expect("Failed to dequeue player");
+            if entry.player_chain_id == own_chain_id {
+                self.record_match(entry.player, room_id, room.host_chain_id).await;
+            } else {
+                self.runtime
+                    .prepare_message(Message::MatchFound {
+                        player: entry.player,
+                        room_id,
+                        host_chain_id: room.host_chain_id,
+                    })
+                    .send_to(entry.player_chain_id);
+            }
+        }
+    }
+    
+    /// Point the player's ticket at their room; a chain hosting it needs the prompt library
+    async fn record_match(&mut self, player: AccountOwner, room_id: CryptoHash, host_chain_id: ChainId) {
+        if let Some(mut ticket) = self.state.match_tickets.get(&player).await.expect("Failed to get ticket") {
+            ticket.room_id = Some(room_id);
+            ticket.host_chain_id = Some(host_chain_id);
+            self.state.match_tickets.insert(&player, ticket).expect("Failed to update ticket");
+        }
+        if host_chain_id == self.runtime.chain_id() {
+            self.subscribe_to_prompts();
+        }
+    }
+    
+    // === Profiles ===
+    
+    /// Set the signer's profile; the display name must be registered to them unless
+    /// it is the placeholder
+    async fn set_profile(&mut self, profile: Profile) -> Result<(), TypeArenaError> {
+        let owner = self.signer()?;
+        profile.validate()?;
+        if profile.display_name != Profile::default_for(&owner).display_name
+            && self.registered_name(&owner).await.as_ref() != Some(&profile.display_name)
+        {
+            return Err(TypeArenaError::NameNotRegistered);
+        }
+        self.store_profile(owner, profile).await;
+        Ok(())
+    }
+    
+    /// Store a profile and pass it on to the hosts of the owner's unfinished rooms
+    async fn store_profile(&mut self, owner: AccountOwner, profile: Profile) {
+        self.state.profiles.insert(&owner, profile.clone()).expect("Failed to store profile");
+        
+        let mut host_chains = Vec::new();
+        self.state.rooms.for_each_index_value(|_room_id, room| {
+            if room.status != RoomStatus::Finished && room.has_player(&owner) {
+                host_chains.push(room.host_chain_id);
+            }
+            Ok(())
+        }).await.expect("Failed to read rooms");
+        host_chains.sort();
+        host_chains.dedup();
+        
+        let own_chain_id = self.runtime.chain_id();
+        for chain_id in host_chains {
+            if chain_id == own_chain_id {
+                self.rename_in_hosted_rooms(&owner, &profile.display_name).await;
+            } else {
+                self.runtime
+                    .prepare_message(Message::ProfileUpdated { owner, profile: profile.clone() })
+                    .send_to(chain_id);
+            }
+        }
+    }
+    
+    /// The owner's stored profile, or the placeholder until they set one
+    async fn profile_of(&self, owner: &AccountOwner) -> Profile {
+        self.state.profiles.get(owner).await
+            .expect("Failed to get profile")
+            .unwrap_or_else(|| Profile::default_for(owner))
+    }
+    
+    /// Update the owner's name in unfinished rooms hosted here and sync the players
+    async fn rename_in_hosted_rooms(&mut self, owner: &AccountOwner, display_name: &str) {
+        let own_chain_id = self.runtime.chain_id();
+        let mut rooms = Vec::new();
+        self.state.rooms.for_each_index_value(|_room_id, room| {
+            if room.host_chain_id == own_chain_id && room.status != RoomStatus::Finished && room.has_player(owner) {
+                rooms.push(room.into_owned());
+            }
+            Ok(())
+        }).await.expect("Failed to read rooms");
+        
+        for mut room in rooms {
+            for player in room.players.iter_mut().filter(|p| &p.owner == owner) {
+                player.display_name = display_name.to_string();
+            }
+            self.broadcast_state_sync(&room).await;
+            self.save_room(room);
+        }
+    }
+    
+    // === Name Registry (kept on the creator chain) ===
+    
+    /// Ask the registry for a unique display name
+    async fn claim_name(&mut self, name: String) -> Result<(), TypeArenaError> {
+        let owner = self.signer()?;
+        if !is_valid_display_name(&name) {
+            return Err(TypeArenaError::InvalidDisplayName);
+        }
+        if is_reserved_name(&name) {
+            return Err(TypeArenaError::ReservedName);
+        }
+        
+        let mut claim = NameClaim {
+            name: name.clone(),
+            status: JoinStatus::Pending,
+            reason: None,
+            requested_at: self.runtime.system_time(),
+        };
+        
+        let owner_chain_id = self.runtime.chain_id();
+        let registry_chain_id = self.runtime.application_creator_chain_id();
+        if owner_chain_id == registry_chain_id {
+            // Register before recording anything, so a refused claim leaves no trace
+            self.handle_claim_name(owner, owner_chain_id, name).await?;
+            claim.status = JoinStatus::Accepted;
+        } else {
+            self.runtime
+                .prepare_message(Message::ClaimName { owner, owner_chain_id, name })
+                .send_to(registry_chain_id);
+        }
+        self.state.name_claims.insert(&owner, claim).expect("Failed to record claim");
+        Ok(())
+    }
+    
+    /// Give up the signer's name and fall back to the placeholder
+    async fn release_name(&mut self) -> Result<(), TypeArenaError> {
+        let owner = self.signer()?;
+        if self.registered_name(&owner).await.is_none() {
+            return Err(TypeArenaError::NameNotRegistered);
+        }
+        self.apply_name_released(owner).await;
+        
+        let registry_chain_id = self.runtime.application_creator_chain_id();
+        if self.runtime.chain_id() == registry_chain_id {
+            self.handle_release_name(owner).await;
+        } else {
+            self.runtime
+                .prepare_message(Message::ReleaseName { owner })
+                .send_to(registry_chain_id);
+        }
+        Ok(())
+    }
+    
+    /// Hand the signer's name to another owner. The name stays in use here until
+    /// the registry confirms with `NameReleased`.
+    async fn transfer_name(&mut self, new_owner: AccountOwner, new_owner_chain_id: ChainId) -> Result<(), TypeArenaError> {
+        let owner = self.signer()?;
+        if self.registered_name(&owner).await.is_none() {
+            return Err(TypeArenaError::NameNotRegistered);
+        }
+        
+        let registry_chain_id = self.runtime.application_creator_chain_id();
+        if self.runtime.chain_id() == registry_chain_id {
+            self.handle_transfer_name(owner, new_owner, new_owner_chain_id).await
+        } else {
+            self.runtime
+                .prepare_message(Message::TransferName { owner, new_owner, new_owner_chain_id })
+                .send_to(registry_chain_id);
+            Ok(())
+        }
+    }
+    
+    /// The name the registry confirmed for this owner, as seen by this chain
+    async fn registered_name(&self, owner: &AccountOwner) -> Option<String> {
+        self.state.registered_names.get(owner).await.expect("Failed to get registered name")
+    }
+    
+    /// Register a name unless a different owner holds it or a look-alike (registry only)
+    async fn handle_claim_name(&mut self, owner: AccountOwner, owner_chain_id: ChainId, name: String) -> Result<(), TypeArenaError> {
+        if !is_valid_display_name(&name) {
+            return Err(TypeArenaError::InvalidDisplayName);
+        }
+        if is_reserved_name(&name) {
+            return Err(TypeArenaError::ReservedName);
+        }
+        let key = normalize_name(&name);
+        if let Some(holder) = self.state.names.get(&key).await.expect("Failed to get name") {
+            if holder.owner != owner {
+                return Err(TypeArenaError::NameTaken);
+            }
+        }
+        
+        self.unregister_name(&owner).await;
+        let entry = RegisteredName {
+            name: name.clone(),
+            owner,
+            owner_chain_id,
+            registered_at: self.runtime.system_time(),
+        };
+        self.state.names.insert(&key, entry).expect("Failed to register name");
+        self.state.names_by_owner.insert(&owner, key).expect("Failed to index name");
+        self.notify_name_holder(owner, owner_chain_id, Some(name)).await;
+        Ok(())
+    }
+    
+    async fn handle_release_name(&mut self, owner: AccountOwner) {
+        self.unregister_name(&owner).await;
+    }
+    
+    /// Move a name to `new_owner` (registry only). Owners who already hold a name are
+    /// refused, so nobody can strip a player's name by pushing another one onto them.
+    async fn handle_transfer_name(&mut self, owner: AccountOwner, new_owner: AccountOwner, new_owner_chain_id: ChainId) -> Result<(), TypeArenaError> {
+        if self.state.names_by_owner.contains_key(&new_owner).await.expect("Failed to get name index") {
+            return Err(TypeArenaError::RecipientHasName);
+        }
+        let Some(mut entry) = self.unregister_name(&owner).await else {
+            return Err(TypeArenaError::NameNotRegistered);
+        };
+        let owner_chain_id = entry.owner_chain_id;
+        
+        let key = normalize_name(&entry.name);
+        let name = entry.name.clone();
+        entry.owner = new_owner;
+        entry.owner_chain_id = new_owner_chain_id;
+        entry.registered_at = self.runtime.system_time();
+        self.state.names.insert(&key, entry).expect("Failed to register name");
+        self.state.names_by_owner.insert(&new_owner, key).expect("Failed to index name");
+        
+        self.notify_name_holder(owner, owner_chain_id, None).await;
+        self.notify_name_holder(new_owner, new_owner_chain_id, Some(name)).await;
+        Ok(())
+    }
+    
+    /// Drop the owner's registry entry, returning it (registry only)
+    async fn unregister_name(&mut self, owner: &AccountOwner) -> Option<RegisteredName> {
+        let key = self.state.names_by_owner.get(owner).await.expect("Failed to get name index")?;
+        self.state.names_by_owner.remove(owner).expect("Failed to remove name index");
+        let entry = self.state.names.get(&key).await.expect("Failed to get name");
+        self.state.names.remove(&key).expect("Failed to release name");
+        entry
+    }
+    
+    /// Tell a player chain which name its owner now holds, if any
+    async fn notify_name_holder(&mut self, owner: AccountOwner, chain_id: ChainId, name: Option<String>) {
+        if chain_id == self.runtime.chain_id() {
+            match name {
+                Some(name) => self.apply_name_claimed(owner, name).await,
+                None => self.apply_name_released(owner).await,
+            }
+        } else {
+            let message = match name {
+                Some(name) => Message::NameClaimed { owner, name },
+                None => Message::NameReleased { owner },
+            };
+            self.runtime.prepare_message(message).send_to(chain_id);
+        }
+    }
+    
+    /// Record a confirmed name and show it in the owner's profile
+    async fn apply_name_claimed(&mut self, owner: AccountOwner, name: String) {
+        self.state.registered_names.insert(&owner, name.clone()).expect("Failed to record name");
+        if let Some(mut claim) = self.state.name_claims.get(&owner).await.expect("Failed to get claim") {
+            if claim.status == JoinStatus::Pending && claim.name == name {
+                claim.status = JoinStatus::Accepted;
+                self.state.name_claims.insert(&owner, claim).expect("Failed to update claim");
+            }
+        }
+        
+        let mut profile = self.profile_of(&owner).await;
+        profile.display_name = name;
+        self.store_profile(owner, profile).await;
+    }
+    
+    /// Forget the owner's name and put the placeholder back in their profile
+    async fn apply_name_released(&mut self, owner: AccountOwner) {
+        self.state.registered_names.remove(&owner).expect("Failed to remove name");
+        
+        let mut profile = self.profile_of(&owner).await;
+        profile.display_name = Profile::default_for(&owner).display_name;
+        self.store_profile(owner, profile).await;
+    }
+    
+    // === Room Directory (kept on the creator chain) ===
+    
+    /// List the room on the directory chain while it is an open lobby, and remove it otherwise
+    async fn publish_listing(&mut self, room: &GameRoom) {
+        // Matchmade rooms are full from the start and never listed
+        if room.matchmade {
+            return;
+        }
+        let directory_chain_id = self.runtime.application_creator_chain_id();
+        let is_directory = self.runtime.chain_id() == directory_chain_id;
+        
+        if room.is_listed() {
+            let listing = room.listing(self.runtime.system_time());
+            if is_directory {
+                self.handle_register_room(listing).await;
+            } else {
+                self.runtime
+                    .prepare_message(Message::RegisterRoom { listing })
+                    .send_to(directory_chain_id);
+            }
+        } else {
+            self.unpublish_listing(room).await;
+        }
+    }
+    
+    async fn unpublish_listing(&mut self, room: &GameRoom) {
+        let directory_chain_id = self.runtime.application_creator_chain_id();
+        if self.runtime.chain_id() == directory_chain_id {
+            self.handle_deregister_room(room.room_id, room.host_epoch).await;
+        } else {
+            self.runtime
+                .prepare_message(Message::DeregisterRoom {
+                    room_id: room.room_id,
+                    host_epoch: room.host_epoch,
+                })
+                .send_to(directory_chain_id);
+        }
+    }
+    
+    /// Whether the sender may change a room's directory entry: the chain that listed it,
+    /// or a newer host after a handover
+    async fn may_update_listing(&self, room_id: &CryptoHash, sender: ChainId, host_chain_id: ChainId, host_epoch: u32) -> bool {
+        match self.state.directory.get(room_id).await.expect("Failed to get listing") {
+            Some(current) => sender == current.host_chain_id || (sender == host_chain_id && host_epoch > current.host_epoch),
+            None => sender == host_chain_id,
+        }
+    }
+    
+    async fn handle_register_room(&mut self, listing: RoomListing) {
+        if self.runtime.chain_id() != self.runtime.application_creator_chain_id() {
+            return;
+        }
+        let sender = self.runtime.message_origin_chain_id().unwrap_or(self.runtime.chain_id());
+        if !self.may_update_listing(&listing.room_id, sender, listing.host_chain_id, listing.host_epoch).await {
+            return;
+        }
+        let room_id = listing.room_id;
+        let order = RoomOrder { created_at: listing.created_at, room_id };
+        self.state.rooms_by_creation.insert(&order).expect("Failed to index room");
+        self.state.directory.insert(&room_id, listing).expect("Failed to list room");
+    }
+    
+    async fn handle_deregister_room(&mut self, room_id: CryptoHash, host_epoch: u32) {
+        if self.runtime.chain_id() != self.runtime.application_creator_chain_id() {
+            return;
+        }
+        let sender = self.runtime.message_origin_chain_id().unwrap_or(self.runtime.chain_id());
+        if !self.may_update_listing(&room_id, sender, sender, host_epoch).await {
+            return;
+        }
+        let Some(listing) = self.state.directory.get(&room_id).await.expect("Failed to get listing") else {
+            return;
+        };
+        self.state.directory.remove(&room_id).expect("Failed to unlist room");
+        self.unindex_room(RoomOrder { created_at: listing.created_at, room_id }).await;
+    }
+    
+    // === Room Expiry ===
+    
+    /// Delete expired rooms along with the join requests and rejections kept for them.
+    /// Finished games stay in `matches`. Each chain expires its own copies, so hosts
+    /// and players need not coordinate.
+    async fn cleanup_rooms(&mut self, limit: Option<u32>) {
+        let parameters = self.runtime.application_parameters();
+        let now = self.runtime.system_time();
+        let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+        if limit == 0 {
+            return;
+        }
+        
+        let mut expired = Vec::new();
+        self.state.rooms.for_each_index_value_while(|_room_id, room| {
+            if room.is_expired(now, parameters.room_idle_expiry_secs, parameters.lobby_max_age_secs) {
+                expired.push(room.into_owned());
+            }
+            Ok(expired.len() < limit)
+        }).await.expect("Failed to read rooms");
+        
+        let own_chain_id = self.runtime.chain_id();
+        for room in &expired {
+            if room.host_chain_id == own_chain_id && room.is_listed() {
+                self.unpublish_listing(room).await;
+            }
+            self.state.rooms.remove(&room.room_id).expect("Failed to remove room");
+            self.state.pending_joins.remove(&room.room_id).expect("Failed to remove join");
+            self.state.rejections.remove(&room.room_id).expect("Failed to remove rejection");
+            self.unindex_room(RoomOrder { created_at: room.created_at, room_id: room.room_id }).await;
+        }
+        
+        // Listings whose host stopped updating them, e.g. a chain that was abandoned
+        let remaining = limit.saturating_sub(expired.len());
+        if own_chain_id == self.runtime.application_creator_chain_id() && remaining > 0 {
+            let mut stale = Vec::new();
+            self.state.directory.for_each_index_value_while(|room_id, listing| {
+                let idle = now > listing.updated_at.saturating_add(TimeDelta::from_secs(parameters.room_idle_expiry_secs));
+                let too_old = now > listing.created_at.saturating_add(TimeDelta::from_secs(parameters.lobby_max_age_secs));
+                if idle || too_old {
+                    stale.push(RoomOrder { created_at: listing.created_at, room_id });
+                }
+                Ok(stale.len() < remaining)
+            }).await.expect("Failed to read directory");
+            for order in stale {
+                self.state.directory.remove(&order.room_id).expect("Failed to unlist room");
+                self.unindex_room(order).await;
+            }
+        }
+    }
+    
+    // === Message Handlers (executed on receiving chain) ===
+    
+    async fn handle_player_joined(&mut self, room_id: CryptoHash, player: AccountOwner, player_chain_id: ChainId, profile: Profile, seed_commitment: CryptoHash, passphrase: Option<String>) -> Result<(), TypeArenaError> {
+        let mut room = self.load_host_room(&room_id).await?;
+        
+        // Validate join
+        if room.status != RoomStatus::Lobby {
+            return Err(TypeArenaError::GameAlreadyStarted);
+        }
+        if room.roster_locked {
+            return Err(TypeArenaError::RosterLocked);
+        }
+        if room.has_player(&player) {
+            return Err(TypeArenaError::AlreadyInRoom);
+        }
+        if room.is_full() {
+            return Err(TypeArenaError::RoomFull);
+        }
+        if room.is_banned(&player, self.runtime.system_time()) {
+            return Err(TypeArenaError::Banned);
+        }
+        if !room.access.admits(room_id, &player, passphrase.as_deref()) {
+            return Err(TypeArenaError::NotInvited);
+        }
+        
+        // Add player
+        room.add_player(player, player_chain_id, profile.display_name.clone(), seed_commitment);
+        self.state.profiles.insert(&player, profile).expect("Failed to store profile");
+        
+        // Sync state to all players
+        self.broadcast_state_sync(&room).await;
+        self.publish_listing(&room).await;
+        
+        self.save_room(room);
+        Ok(())
+    }
+    
+    async fn handle_join_answered(&mut self, room_id: CryptoHash, status: JoinStatus, reason: Option<TypeArenaError>) {
+        if let Some(mut pending) = self.state.pending_joins.get(&room_id).await.expect("Failed to get join") {
+            pending.status = status;
+            pending.reason = reason;
+            self.state.pending_joins.insert(&room_id, pending).expect("Failed to update join");
+        }
+    }
+    
+    async fn process_seed_commitment(&mut self, room_id: CryptoHash, player: AccountOwner, seed_commitment: CryptoHash) -> Result<(), TypeArenaError> {
+        let mut room = self.load_host_room(&room_id).await?;
+        
+        if room.status != RoomStatus::Lobby {
+            return Err(TypeArenaError::GameAlreadyStarted);
+        }
+        room.commit_seed(&player, seed_commitment)?;
+        
+        self.broadcast_state_sync(&room).await;
+        
+        self.save_room(room);
+        Ok(())
+    }
+    
+    async fn process_seed_reveal(&mut self, room_id: CryptoHash, player: AccountOwner, secret: Vec<u8>) -> Result<(), TypeArenaError> {
+        let mut room = self.load_host_room(&room_id).await?;
+        
+        if room.status != RoomStatus::Lobby {
+            return Err(TypeArenaError::GameAlreadyStarted);
+        }
+        room.reveal_seed(&player, secret)?;
+        self.maybe_auto_start(&mut room).await;
+        
+        self.broadcast_state_sync(&room).await;
+        
+        self.save_room(room);
+        Ok(())
+    }
+    
+    async fn process_ready_change(&mut self, room_id: CryptoHash, player: AccountOwner, ready: bool) -> Result<(), TypeArenaError> {
+        let mut room = self.load_host_room(&room_id).await?;
+        
+        if room.status != RoomStatus::Lobby {
+            return Err(TypeArenaError::GameAlreadyStarted);
+        }
+        room.set_ready(&player, ready)?;
+        self.maybe_auto_start(&mut room).await;
+        
+        // Ready marks reach the other players through the state sync; a locked
+        // roster leaves the directory until it reopens
+        self.broadcast_state_sync(&room).await;
+        self.publish_listing(&room).await;
+        
+        self.save_room(room);
+        Ok(())
+    }
+    
+    async fn handle_game_started(&mut self, room_id: CryptoHash, prompt: String, round: u8, start_time: Timestamp) {
+        // Create or update room on player's chain
+        if let Some(mut room) = self.state.rooms.get(&room_id).await.expect("Failed to get room") {
+            if !self.is_from_host(&room) {
+                return;
+            }
+            room.status = RoomStatus::InProgress;
+            room.current_round = round;
+            room.current_prompt = Some(prompt);
+            room.round_start_time = Some(start_time);
+            room.round_results.clear();
+            room.last_host_activity = self.runtime.system_time();
+            self.save_room(room);
+        }
+    }
+    
+    async fn handle_result_submitted(&mut self, room_id: CryptoHash, player: AccountOwner, keystrokes: Vec<u8>) -> Result<(), TypeArenaError> {
+        // Only process on host chain
+        self.process_result(room_id, player, keystrokes).await
+    }
+    
+    async fn process_result(&mut self, room_id: CryptoHash, player: AccountOwner, keystrokes: Vec<u8>) -> Result<(), TypeArenaError> {
+        let mut room = self.load_host_room(&room_id).await?;
+        
+        if room.status != RoomStatus::InProgress {
+            return Err(TypeArenaError::GameNotInProgress);
+        }
+        
+        // Only players in the room may submit
+        let player_name = room.players.iter()
+            .find(|p| p.owner == player)
+            .map(|p| p.display_name.clone())
+            .ok_or(TypeArenaError::NotInRoom)?;
+        
+        // Check if player already submitted
+        if room.round_results.iter().any(|r| r.player == player) {
+            return Err(TypeArenaError::AlreadySubmitted);
+        }
+        
+        // Late results are rejected once the round deadline has passed
+        let now = self.runtime.system_time();
+        if !room.has_round_started(now) {
+            return Err(TypeArenaError::RoundNotStarted);
+        }
+        if room.is_round_expired(now) {
+            return Err(TypeArenaError::RoundExpired);
+        }
+        
+        let prompt = room.current_prompt.as_ref().ok_or(TypeArenaError::GameNotInProgress)?;
+        
+        // Rebuild the typed text and timing from the keystroke log
+        let Replay { text: typed_text, time_ms } = KeystrokeLog::decode(&keystrokes).and_then(|log| log.replay())?;
+        // Offsets count from the reveal, so a log longer than the time since then was typed early
+        if time_ms == 0 || time_ms > room.round_time_limit_secs as u64 * 1000 || time_ms > room.round_elapsed_ms(now) {
+            return Err(TypeArenaError::ImplausibleKeystrokes);
+        }
+        
+        // Score against the prompt on chain instead of trusting client-reported numbers
+        let correct_chars = PlayerResult::count_correct_chars(prompt, &typed_text);
+        let wpm = PlayerResult::calculate_wpm(correct_chars, time_ms);
+        let accuracy = PlayerResult::calculate_accuracy(correct_chars, prompt, &typed_text);
+        let scoring = self.runtime.application_parameters().scoring;
+        let score = PlayerResult::calculate_score(wpm, accuracy, scoring);
+        
+        let result = PlayerResult {
+            player,
+            player_name,
+            wpm,
+            accuracy,
+            time_ms,
+            score,
+            dnf: false,
+        };
+        
+        room.round_results.push(result);
+        // Fold the log into the draw of later prompts
+        let bytes = bcs::to_bytes(&(room.round_entropy, player, &keystrokes)).expect("Serialization failed");
+        room.round_entropy = Some(CryptoHashGen::new(&SeedWrapper(bytes)));
+        
+        // Update player's game score
+        if let Some(game_score) = room.game_scores.iter_mut().find(|s| s.player == player) {
+            game_score.total_score += score;
+            game_score.rounds_completed += 1;
+        }
+        
+        // Update player stats
+        let mut stats = self.state.player_stats.get(&player).await
+            .expect("Failed to get stats")
+            .unwrap_or_default();
+        stats.update_with_result(wpm, accuracy);
+        self.save_stats(player, stats).await;
+        
+        // Check if all players submitted
+        if room.all_results_submitted() {
+            self.end_round(&mut room).await;
+        }
+        
+        self.save_room(room);
+        Ok(())
+    }
+    
+    async fn end_round(&mut self, room: &mut GameRoom) {
+        let results = room.round_results.clone();
+        room.record_round(room.current_round, results.clone(), self.runtime.system_time());
+        
+        if room.current_round >= room.total_rounds {
+            // Game over
+            room.status = RoomStatus::Finished;
+            
+            // Find winner (highest total score among those who stayed)
+            let Some(winner) = room.game_scores.iter()
+                .filter(|s| !s.forfeited)
+                .max_by_key(|s| s.total_score)
+                .map(|s| s.player)
+            else {
+                // Everyone left before the end, so there is nobody to rank or notify
+                return;
+            };
+            
+            // Final standings keep players who left, marked DNF as forfeits
+            let mut final_standings = Vec::new();
+            for score in &room.game_scores {
+                let player_name = match room.players.iter().find(|p| p.owner == score.player) {
+                    Some(player_info) => player_info.display_name.clone(),
+                    None => self.profile_of(&score.player).await.display_name,
+                };
+                final_standings.push(PlayerResult {
+                    player: score.player,
+                    player_name,
+                    wpm: 0,
                     accuracy: 0,
                     time_ms: 0,
-                    score: s.total_score,
+                    score: score.total_score,
+                    dnf: score.forfeited,
+                });
+            }
+            
+            // Update game counts of everyone who took part, forfeits included
+            for result in &final_standings {
+                let mut stats = self.state.player_stats.get(&result.player).await
+                    .expect("Failed to get stats")
+                    .unwrap_or_default();
+                stats.games_played += 1;
+                if result.player == winner {
+                    stats.games_won += 1;
+                }
+                self.save_stats(result.player, stats).await;
+            }
+            
+            // Matchmade games are rated once, on the matchmaker's ladder
+            let matchmaker_chain_id = self.runtime.application_creator_chain_id();
+            if !room.matchmade || matchmaker_chain_id == self.runtime.chain_id() {
+                self.update_ratings(room.room_id, &final_standings).await;
+            }
+            
+            // Increment total games
+            let total = self.state.total_games.get();
+            self.state.total_games.set(*total + 1);
+            
+            // The matchmaker keeps its own ladder for the games it formed
+            if room.matchmade {
+                if matchmaker_chain_id == self.runtime.chain_id() {
+                    self.state.open_matches.remove(&room.room_id).expect("Failed to close match");
+                } else {
+                    self.runtime
+                        .prepare_message(Message::MatchFinished {
+                            room_id: room.room_id,
+                            final_standings: final_standings.clone(),
+                            winner,
+                        })
+                        .send_to(matchmaker_chain_id);
                 }
-            }).collect();
+            }
+            
+            self.archive_match(room, &final_standings, winner).await;
             
+            // The last round's results go out first, without a next prompt
+            let round_ended = Message::RoundEnded {
+                room_id: room.room_id,
+                round: room.current_round,
+                results,
+                next_prompt: None,
+                start_time: self.runtime.system_time(),
+            };
             let message = Message::GameEnded {
                 room_id: room.room_id,
                 final_standings,
@@ -381,6 +1722,9 @@ impl TypeArenaContract {
             
             for player_info in &room.players {
                 if player_info.chain_id != self.runtime.chain_id() {
+                    self.runtime
+                        .prepare_message(round_ended.clone())
+                        .send_to(player_info.chain_id);
                     self.runtime
                         .prepare_message(message.clone())
                         .send_to(player_info.chain_id);
@@ -391,15 +1735,21 @@ impl TypeArenaContract {
             room.current_round += 1;
             room.round_results.clear();
             
-            let next_prompt = self.get_random_prompt();
+            // Keep the previous prompt if the matching ones were retired mid-game
+            let next_prompt = match self.get_random_prompt(room).await {
+                Ok(prompt) => prompt,
+                Err(_) => room.current_prompt.clone().unwrap_or_default(),
+            };
+            let start_time = self.round_start_after_countdown();
             room.current_prompt = Some(next_prompt.clone());
-            room.round_start_time = Some(self.runtime.system_time());
+            room.round_start_time = Some(start_time);
             
             let message = Message::RoundEnded {
                 room_id: room.room_id,
                 round: room.current_round - 1,
                 results,
                 next_prompt: Some(next_prompt),
+                start_time,
             };
             
             for player_info in &room.players {
@@ -410,40 +1760,437 @@ impl TypeArenaContract {
                 }
             }
         }
+        
+        // Keep player copies current so any of them can take over as host
+        self.broadcast_state_sync(room).await;
+    }
+    
+    /// Rate a finished game as one Glicko-2 period in which every player met every other,
+    /// with the higher total score winning each pairing and equal scores drawing.
+    /// A forfeit loses to everyone who finished.
+    async fn update_ratings(&mut self, room_id: CryptoHash, final_standings: &[PlayerResult]) {
+        let mut standings = Vec::new();
+        for result in final_standings {
+            let stats = self.state.player_stats.get(&result.player).await
+                .expect("Failed to get stats")
+                .unwrap_or_default();
+            standings.push((result.player, (!result.dnf, result.score), stats));
+        }
+        
+        let now = self.runtime.system_time();
+        for (player, placing, stats) in &standings {
+            let outcomes: Vec<(Rating, f64)> = standings.iter()
+                .filter(|(opponent, _, _)| opponent != player)
+                .map(|(_, opponent_placing, opponent_stats)| {
+                    let outcome = match placing.cmp(opponent_placing) {
+                        Ordering::Greater => 1.0,
+                        Ordering::Equal => 0.5,
+                        Ordering::Less => 0.0,
+                    };
+                    (opponent_stats.rating, outcome)
+                })
+                .collect();
+            
+            let mut stats = stats.clone();
+            let previous = stats.rating;
+            stats.rating = previous.updated(&outcomes);
+            
+            self.state.rating_history.load_entry_mut(player).await
+                .expect("Failed to load rating history")
+                .push(RatingChange {
+                    room_id,
+                    rating: stats.rating,
+                    delta: stats.rating.rating - previous.rating,
+                    timestamp: now,
+                });
+            self.save_stats(*player, stats).await;
+        }
     }
     
-    async fn handle_round_ended(&mut self, room_id: CryptoHash, _round: u8, _results: Vec<PlayerResult>, next_prompt: Option<String>) {
+    /// End the current round once its time limit has passed, scoring missing players as DNF
+    async fn close_expired_round(&mut self, room_id: CryptoHash) -> Result<(), TypeArenaError> {
+        let mut room = self.load_host_room(&room_id).await?;
+        
+        if room.status != RoomStatus::InProgress {
+            return Err(TypeArenaError::GameNotInProgress);
+        }
+        if !room.is_round_expired(self.runtime.system_time()) {
+            return Err(TypeArenaError::RoundNotExpired);
+        }
+        
+        room.mark_missing_as_dnf();
+        self.end_round(&mut room).await;
+        
+        self.save_room(room);
+        Ok(())
+    }
+    
+    async fn handle_round_ended(&mut self, room_id: CryptoHash, round: u8, results: Vec<PlayerResult>, next_prompt: Option<String>, start_time: Timestamp) {
         if let Some(mut room) = self.state.rooms.get(&room_id).await.expect("Failed to get room") {
-            room.current_round += 1;
-            room.round_results.clear();
-            room.current_prompt = next_prompt;
-            room.round_start_time = Some(self.runtime.system_time());
-            self.state.rooms.insert(&room_id, room).expect("Failed to update room");
+            if !self.is_from_host(&room) {
+                return;
+            }
+            let now = self.runtime.system_time();
+            room.record_round(round, results, now);
+            // Without a next prompt the game is over and `GameEnded` follows
+            if let Some(prompt) = next_prompt {
+                room.current_round += 1;
+                room.round_results.clear();
+                room.current_prompt = Some(prompt);
+                room.round_start_time = Some(start_time);
+            }
+            room.last_host_activity = now;
+            self.save_room(room);
+        }
+    }
+    
+    /// Append a finished game to the match log and index it under each participant
+    async fn archive_match(&mut self, room: &GameRoom, final_standings: &[PlayerResult], winner: AccountOwner) {
+        let match_id = self.state.matches.count() as u64;
+        let participants: Vec<AccountOwner> = final_standings.iter().map(|result| result.player).collect();
+        for participant in &participants {
+            self.state.player_matches.load_entry_mut(participant).await
+                .expect("Failed to load match index")
+                .push(match_id);
         }
+        self.state.matches.push(MatchRecord {
+            match_id,
+            room_id: room.room_id,
+            host: room.host,
+            participants,
+            final_standings: final_standings.to_vec(),
+            rounds: room.round_history.clone(),
+            winner,
+            matchmade: room.matchmade,
+            created_at: room.created_at,
+            started_at: room.round_history.first().and_then(|r| r.start_time),
+            ended_at: self.runtime.system_time(),
+        });
     }
     
-    async fn handle_game_ended(&mut self, room_id: CryptoHash, _final_standings: Vec<PlayerResult>, _winner: AccountOwner) {
+    async fn handle_game_ended(&mut self, room_id: CryptoHash, final_standings: Vec<PlayerResult>, winner: AccountOwner) {
         if let Some(mut room) = self.state.rooms.get(&room_id).await.expect("Failed to get room") {
+            if !self.is_from_host(&room) {
+                return;
+            }
+            // Players sharing a chain each get a copy of the message; archive once
+            if room.status != RoomStatus::Finished {
+                self.archive_match(&room, &final_standings, winner).await;
+            }
             room.status = RoomStatus::Finished;
-            self.state.rooms.insert(&room_id, room).expect("Failed to update room");
+            room.last_host_activity = self.runtime.system_time();
+            self.save_room(room);
         }
     }
     
-    async fn handle_player_left(&mut self, room_id: CryptoHash, player: AccountOwner) {
+    async fn handle_player_left(&mut self, room_id: CryptoHash, player: AccountOwner) -> Result<(), TypeArenaError> {
+        let mut room = self.load_host_room(&room_id).await?;
+        
+        if !room.has_player(&player) {
+            return Err(TypeArenaError::NotInRoom);
+        }
+        
+        if room.status == RoomStatus::InProgress {
+            room.forfeit(&player);
+        } else {
+            room.remove_player(&player);
+        }
+        
+        // Nobody is left to play or take over, so drop the room
+        if room.players.is_empty() {
+            if !room.matchmade {
+                self.unpublish_listing(&room).await;
+            }
+            self.state.rooms.remove(&room_id).expect("Failed to remove room");
+            self.unindex_room(RoomOrder { created_at: room.created_at, room_id }).await;
+            return Ok(());
+        }
+        
+        // The round may have been waiting only on the departing player; end it
+        // before any handover, while this chain is still the host
+        if room.status == RoomStatus::InProgress && room.all_results_submitted() {
+            self.end_round(&mut room).await;
+        }
+        
+        // A departing host hands the room to the next player in line
+        if player == room.host {
+            if let Some(next_host) = room.players.first().map(|p| p.owner) {
+                self.hand_over_host(&mut room, next_host);
+            }
+        } else {
+            // The departing player may have been the last one not ready
+            self.maybe_auto_start(&mut room).await;
+        }
+        
+        // Broadcast state sync
+        self.broadcast_state_sync(&room).await;
+        self.publish_listing(&room).await;
+        
+        self.save_room(room);
+        Ok(())
+    }
+    
+    async fn handle_profile_updated(&mut self, owner: AccountOwner, profile: Profile) {
+        self.rename_in_hosted_rooms(&owner, &profile.display_name).await;
+        self.state.profiles.insert(&owner, profile).expect("Failed to store profile");
+    }
+    
+    /// Mark the local copy of a room this chain's player was removed from
+    async fn handle_player_removed(&mut self, room_id: CryptoHash, player: AccountOwner, until: Option<Timestamp>) {
         if let Some(mut room) = self.state.rooms.get(&room_id).await.expect("Failed to get room") {
-            room.players.retain(|p| p.owner != player);
-            room.game_scores.retain(|s| s.player != player);
-            
-            // Broadcast state sync
-            self.broadcast_state_sync(&room).await;
-            
-            self.state.rooms.insert(&room_id, room).expect("Failed to update room");
+            if !self.is_from_host(&room) {
+                return;
+            }
+            room.remove_player(&player);
+            room.ban(player, until);
+            self.save_room(room);
         }
     }
     
     async fn handle_sync_state(&mut self, room_id: CryptoHash, state_bytes: Vec<u8>) {
-        if let Ok(state) = bcs::from_bytes::<GameRoom>(&state_bytes) {
-            self.state.rooms.insert(&room_id, state).expect("Failed to sync room state");
+        if let Ok(mut state) = bcs::from_bytes::<GameRoom>(&state_bytes) {
+            // Ignore stale state from a host that has since been replaced, and state from
+            // a claimant that lost a same-epoch race to the current host
+            if let Some(current) = self.state.rooms.get(&room_id).await.expect("Failed to get room") {
+                let origin = self.runtime.message_origin_chain_id();
+                let from_new_host = origin == Some(state.host_chain_id) && current.accepts_host(&state.host, state.host_epoch);
+                if state.host_epoch < current.host_epoch || !(self.is_from_host(&current) || from_new_host) {
+                    return;
+                }
+            }
+            state.last_host_activity = self.runtime.system_time();
+            self.save_room(state);
+        }
+    }
+    
+    async fn handle_host_changed(&mut self, room_id: CryptoHash, host: AccountOwner, host_chain_id: ChainId, host_epoch: u32) {
+        if let Some(mut room) = self.state.rooms.get(&room_id).await.expect("Failed to get room") {
+            if !room.accepts_host(&host, host_epoch) {
+                return;
+            }
+            room.host = host;
+            room.host_chain_id = host_chain_id;
+            room.host_epoch = host_epoch;
+            room.last_host_activity = self.runtime.system_time();
+            self.save_room(room);
+        }
+    }
+    
+    /// Register a subscriber and send it the full library (creator chain only)
+    async fn handle_subscribe_prompts(&mut self) {
+        let Some(origin) = self.runtime.message_origin_chain_id() else {
+            return;
+        };
+        if self.runtime.chain_id() != self.runtime.application_creator_chain_id() {
+            return;
+        }
+        self.state.prompt_subscribers.insert(&origin).expect("Failed to add subscriber");
+        
+        let mut prompts = Vec::new();
+        self.state.prompts.for_each_index_value(|_id, prompt| {
+            prompts.push(prompt.into_owned());
+            Ok(())
+        }).await.expect("Failed to read prompts");
+        self.runtime
+            .prepare_message(Message::PromptsUpdated { prompts })
+            .send_to(origin);
+    }
+    
+    /// Apply library updates, trusting only the creator chain
+    fn handle_prompts_updated(&mut self, prompts: Vec<Prompt>) {
+        if self.runtime.message_origin_chain_id() != Some(self.runtime.application_creator_chain_id()) {
+            return;
+        }
+        for prompt in prompts {
+            let id = prompt.id;
+            self.state.prompts.insert(&id, prompt).expect("Failed to update prompt");
+        }
+    }
+    
+    /// Accept match notifications only from the matchmaker
+    async fn handle_match_found(&mut self, player: AccountOwner, room_id: CryptoHash, host_chain_id: ChainId) {
+        if self.runtime.message_origin_chain_id() != Some(self.runtime.application_creator_chain_id()) {
+            return;
+        }
+        self.record_match(player, room_id, host_chain_id).await;
+    }
+    
+    /// Accept name updates only from the registry
+    async fn handle_name_claimed(&mut self, owner: AccountOwner, name: String) {
+        if self.runtime.message_origin_chain_id() != Some(self.runtime.application_creator_chain_id()) {
+            return;
+        }
+        self.apply_name_claimed(owner, name).await;
+    }
+    
+    async fn handle_name_released(&mut self, owner: AccountOwner) {
+        if self.runtime.message_origin_chain_id() != Some(self.runtime.application_creator_chain_id()) {
+            return;
+        }
+        self.apply_name_released(owner).await;
+    }
+    
+    /// Mark a pending claim as refused. Registered names are tracked separately, so a
+    /// refused claim or transfer leaves the current one in place.
+    async fn handle_name_rejected(&mut self, owner: AccountOwner, reason: TypeArenaError) {
+        if let Some(mut claim) = self.state.name_claims.get(&owner).await.expect("Failed to get claim") {
+            if claim.status == JoinStatus::Pending {
+                claim.status = JoinStatus::Rejected;
+            }
+            claim.reason = Some(reason);
+            self.state.name_claims.insert(&owner, claim).expect("Failed to update claim");
+        }
+    }
+    
+    async fn handle_queue_rejected(&mut self, player: AccountOwner, reason: TypeArenaError) {
+        if let Some(mut ticket) = self.state.match_tickets.get(&player).await.expect("Failed to get ticket") {
+            ticket.reason = Some(reason);
+            self.state.match_tickets.insert(&player, ticket).expect("Failed to update ticket");
+        }
+    }
+    
+    /// Rate a matchmade game once, on the word of one of its player chains (matchmaker only)
+    async fn handle_match_finished(&mut self, room_id: CryptoHash, final_standings: Vec<PlayerResult>, winner: AccountOwner) {
+        let Some(origin) = self.runtime.message_origin_chain_id() else {
+            return;
+        };
+        let Some(player_chains) = self.state.open_matches.get(&room_id).await.expect("Failed to get match") else {
+            return;
+        };
+        if !player_chains.contains(&origin) {
+            return;
+        }
+        self.state.open_matches.remove(&room_id).expect("Failed to close match");
+        
+        // The host chain keeps the per-round stats; count the game here so the
+        // players show up on this chain's rating leaderboard
+        for result in &final_standings {
+            let mut stats = self.state.player_stats.get(&result.player).await
+                .expect("Failed to get stats")
+                .unwrap_or_default();
+            stats.games_played += 1;
+            if result.player == winner {
+                stats.games_won += 1;
+            }
+            self.save_stats(result.player, stats).await;
+        }
+        
+        self.update_ratings(room_id, &final_standings).await;
+    }
+    
+    /// Record a rejection sent back by a host chain
+    fn handle_rejected(&mut self, room_id: CryptoHash, error: TypeArenaError) {
+        self.state.rejections.insert(&room_id, error).expect("Failed to record rejection");
+    }
+    
+    /// Send a typed rejection back to the chain whose message failed
+    fn reject(&mut self, room_id: CryptoHash, error: TypeArenaError) {
+        if let Some(origin) = self.runtime.message_origin_chain_id() {
+            if origin != self.runtime.chain_id() {
+                self.runtime
+                    .prepare_message(Message::Rejected { room_id, error })
+                    .send_to(origin);
+            }
+        }
+    }
+    
+    fn signer(&mut self) -> Result<AccountOwner, TypeArenaError> {
+        self.runtime.authenticated_signer().ok_or(TypeArenaError::NotAuthenticated)
+    }
+    
+    /// Store a room, stamping it as active now
+    fn save_room(&mut self, mut room: GameRoom) {
+        room.last_activity = self.runtime.system_time();
+        let room_id = room.room_id;
+        let order = RoomOrder { created_at: room.created_at, room_id };
+        self.state.rooms_by_creation.insert(&order).expect("Failed to index room");
+        self.state.rooms.insert(&room_id, room).expect("Failed to update room");
+    }
+    
+    /// Drop a room from `rooms_by_creation` once neither its room nor its listing is stored
+    async fn unindex_room(&mut self, order: RoomOrder) {
+        let room_id = order.room_id;
+        let has_room = self.state.rooms.contains_key(&room_id).await.expect("Failed to get room");
+        let has_listing = self.state.directory.contains_key(&room_id).await.expect("Failed to get listing");
+        if !has_room && !has_listing {
+            self.state.rooms_by_creation.remove(&order).expect("Failed to unindex room");
+        }
+    }
+    
+    /// Store a player's stats, moving them to their new place in the leaderboard indexes
+    async fn save_stats(&mut self, player: AccountOwner, stats: PlayerStats) {
+        if let Some(previous) = self.state.player_stats.get(&player).await.expect("Failed to get stats") {
+            self.state.players_by_wins.remove(&PlayerRank::by_wins(player, &previous)).expect("Failed to unindex stats");
+            self.state.players_by_rating.remove(&PlayerRank::by_rating(player, &previous)).expect("Failed to unindex stats");
+        }
+        if stats.games_played > 0 {
+            self.state.players_by_wins.insert(&PlayerRank::by_wins(player, &stats)).expect("Failed to index stats");
+            self.state.players_by_rating.insert(&PlayerRank::by_rating(player, &stats)).expect("Failed to index stats");
+        }
+        self.state.player_stats.insert(&player, stats).expect("Failed to update stats");
+    }
+    
+    async fn load_room(&self, room_id: &CryptoHash) -> Result<GameRoom, TypeArenaError> {
+        self.state.rooms.get(room_id).await
+            .expect("Failed to get room")
+            .ok_or(TypeArenaError::RoomNotFound)
+    }
+    
+    /// Whether the message being executed was sent by the room's current host chain.
+    /// Round messages from any other chain are dropped; the state sync that follows
+    /// every round catches up a copy that has not heard of a handover yet.
+    fn is_from_host(&mut self, room: &GameRoom) -> bool {
+        self.runtime.message_origin_chain_id() == Some(room.host_chain_id)
+    }
+    
+    /// Load a room this chain is authoritative for
+    async fn load_host_room(&mut self, room_id: &CryptoHash) -> Result<GameRoom, TypeArenaError> {
+        let room = self.load_room(room_id).await?;
+        if room.host_chain_id != self.runtime.chain_id() {
+            return Err(TypeArenaError::NotHostChain);
+        }
+        Ok(room)
+    }
+    
+    /// Move authority to `new_host`'s chain: the new host receives the full state,
+    /// everyone else gets the new host pointer
+    fn hand_over_host(&mut self, room: &mut GameRoom, new_host: AccountOwner) {
+        let new_host_chain_id = room.get_player_chain(&new_host).expect("New host is not in the room");
+        room.host = new_host;
+        room.host_chain_id = new_host_chain_id;
+        room.host_epoch += 1;
+        
+        let state_bytes = bcs::to_bytes(&*room).expect("Failed to serialize room");
+        self.runtime
+            .prepare_message(Message::HostTransferred {
+                room_id: room.room_id,
+                state_bytes,
+            })
+            .send_to(new_host_chain_id);
+        
+        self.announce_host(room, None);
+    }
+    
+    /// Send `HostChanged` to every player chain (and `also_notify`) other than the host's own
+    fn announce_host(&mut self, room: &GameRoom, also_notify: Option<ChainId>) {
+        let message = Message::HostChanged {
+            room_id: room.room_id,
+            host: room.host,
+            host_chain_id: room.host_chain_id,
+            host_epoch: room.host_epoch,
+        };
+        
+        let own_chain_id = self.runtime.chain_id();
+        let mut recipients: Vec<ChainId> = room.players.iter().map(|p| p.chain_id).chain(also_notify).collect();
+        recipients.sort();
+        recipients.dedup();
+        
+        for chain_id in recipients {
+            if chain_id != own_chain_id && chain_id != room.host_chain_id {
+                self.runtime
+                    .prepare_message(message.clone())
+                    .send_to(chain_id);
+            }
         }
     }
     
@@ -463,17 +2210,85 @@ impl TypeArenaContract {
         }
     }
     
-    fn get_random_prompt(&mut self) -> String {
-        // Generate deterministic but unpredictable prompt selection
-        let data = (
-            self.runtime.chain_id(),
-            self.runtime.system_time(),
-            self.runtime.block_height(),
-        );
-        let bytes = bcs::to_bytes(&data).expect("Serialization failed");
-        let seed = SeedWrapper(bytes);
-        let hash = CryptoHashGen::new(&seed);
-        let index = (hash.as_bytes()[0] as usize) % WORD_BANK.len();
-        WORD_BANK[index].to_string()
+    /// Pick a prompt matching the room's filter that has not been used this game,
+    /// record it on the room, or fail if nothing matches
+    async fn get_random_prompt(&mut self, room: &mut GameRoom) -> Result<String, TypeArenaError> {
+        // Derive the draw from the players' combined seed, the round and the logs
+        // submitted so far, so the host chain's block timing has no influence on which
+        // prompt comes up and later prompts stay unknown until earlier rounds close
+        let prompt_seed = room.prompt_seed.ok_or(TypeArenaError::SeedsNotRevealed)?;
+        let bytes = bcs::to_bytes(&(prompt_seed, room.current_round, room.round_entropy)).expect("Serialization failed");
+        let hash = CryptoHashGen::new(&SeedWrapper(bytes));
+        
+        // The current prompt has been revealed already, so it is never drawn again
+        let candidates: Vec<_> = self.matching_prompts(&room.prompt_filter).await?
+            .into_iter()
+            .filter(|(_, text)| room.current_prompt.as_ref() != Some(text))
+            .collect();
+        let mut unused: Vec<_> = candidates.iter()
+            .filter(|(id, _)| !room.used_prompt_ids.contains(id))
+            .cloned()
+            .collect();
+        if unused.is_empty() {
+            // Every matching prompt was used: allow repeats
+            unused = candidates;
+        }
+        if unused.is_empty() {
+            return Err(TypeArenaError::NoMatchingPrompts);
+        }
+        
+        let index = Self::uniform_index(hash, unused.len());
+        let (id, text) = unused.swap_remove(index);
+        room.used_prompt_ids.push(id);
+        Ok(text)
+    }
+    
+    /// When the next round's prompt is revealed, after the configured countdown
+    fn round_start_after_countdown(&mut self) -> Timestamp {
+        let countdown = self.runtime.application_parameters().countdown_secs;
+        self.runtime.system_time().saturating_add(TimeDelta::from_secs(countdown as u64))
+    }
+    
+    /// Hash of every player's revealed secret, in join order
+    fn combine_seeds(room: &GameRoom) -> CryptoHash {
+        let secrets: Vec<(AccountOwner, &[u8])> = room.players.iter()
+            .map(|p| (p.owner, p.seed_secret.as_deref().unwrap_or_default()))
+            .collect();
+        let bytes = bcs::to_bytes(&secrets).expect("Serialization failed");
+        CryptoHashGen::new(&SeedWrapper(bytes))
+    }
+    
+    /// Map a hash to `0..len` without modulo bias, rehashing when the draw
+    /// falls into the uneven tail of the `u64` range
+    fn uniform_index(mut hash: CryptoHashGen, len: usize) -> usize {
+        let len = len as u64;
+        let limit = u64::MAX - u64::MAX % len;
+        loop {
+            let bytes: [u8; 8] = hash.as_bytes()[..8].try_into().expect("Hash is at least 8 bytes");
+            let value = u64::from_le_bytes(bytes);
+            if value < limit {
+                return (value % len) as usize;
+            }
+            hash = CryptoHashGen::new(&SeedWrapper(hash.as_bytes().to_vec()));
+        }
+    }
+    
+    /// Active library prompts matching `filter`, with their ids. Fails until this
+    /// chain has received the library from the creator chain.
+    async fn matching_prompts(&self, filter: &PromptFilter) -> Result<Vec<(u32, String)>, TypeArenaError> {
+        let mut prompts = Vec::new();
+        let mut library_size = 0;
+        self.state.prompts.for_each_index_value(|id, prompt| {
+            library_size += 1;
+            if !prompt.retired && filter.matches(&prompt.tags) {
+                prompts.push((id, prompt.into_owned().text));
+            }
+            Ok(())
+        }).await.expect("Failed to read prompts");
+        
+        if library_size == 0 {
+            return Err(TypeArenaError::LibraryNotSynced);
+        }
+        Ok(prompts)
     }
 }
@@ -1,9 +1,12 @@
+use std::cmp::Reverse;
+
 use async_graphql::{Enum, InputObject, Request, Response, SimpleObject};
 use linera_sdk::{
     abi::{ContractAbi, ServiceAbi},
     graphql::GraphQLMutationRoot,
     linera_base_types::{AccountOwner, ChainId, CryptoHash, Timestamp},
 };
+use linera_base::crypto::BcsHashable;
 use serde::{Deserialize, Serialize};
 
 /// Type Arena Application ABI
@@ -11,7 +14,7 @@ pub struct TypeArenaAbi;
 
 impl ContractAbi for TypeArenaAbi {
     type Operation = Operation;
-    type Response = ();
+    type Response = Result<(), TypeArenaError>;
 }
 
 impl ServiceAbi for TypeArenaAbi {
@@ -19,38 +22,217 @@ impl ServiceAbi for TypeArenaAbi {
     type QueryResponse = Response;
 }
 
+/// Rules fixed when the application is deployed. Missing JSON fields use the defaults.
+#[derive(Debug, Deserialize, Serialize, Clone, SimpleObject)]
+#[serde(default)]
+pub struct TypeArenaParameters {
+    /// Largest `max_players` a room may be created with
+    pub max_players: u8,
+    /// Largest number of rounds a room may be created with
+    pub max_rounds: u8,
+    /// Round time limit used when `CreateRoom` does not specify one
+    pub default_round_time_limit_secs: u32,
+    /// Delay between announcing a round and revealing its prompt
+    pub countdown_secs: u32,
+    pub scoring: ScoringFormula,
+    /// Rooms with no activity for this long are deleted by `CleanupRooms`. Must cover
+    /// the longest round (600s) plus the countdown and `HOST_TIMEOUT_SECS`.
+    pub room_idle_expiry_secs: u64,
+    /// Lobbies still waiting this long after creation are deleted by `CleanupRooms`;
+    /// at least 600s
+    pub lobby_max_age_secs: u64,
+}
+
+impl Default for TypeArenaParameters {
+    fn default() -> Self {
+        Self {
+            max_players: 8,
+            max_rounds: 10,
+            default_round_time_limit_secs: 60,
+            countdown_secs: 5,
+            scoring: ScoringFormula::AccuracySquared,
+            room_idle_expiry_secs: 86_400,
+            lobby_max_age_secs: 7_200,
+        }
+    }
+}
+
+/// Argument passed when the application is created
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct InstantiationArgument {
+    /// First member of the admin allowlist for the prompt library; defaults to
+    /// the signer creating the application
+    pub admin: Option<AccountOwner>,
+    /// Initial prompt set; `WORD_BANK` is used when empty
+    pub prompts: Vec<String>,
+}
+
+/// Version of the formula turning WPM and accuracy into a round score
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum ScoringFormula {
+    /// WPM * (accuracy/100)^2, heavily rewards accuracy (competitive)
+    AccuracySquared,
+    /// WPM * accuracy/100 (casual)
+    AccuracyLinear,
+}
+
+/// Room options chosen by the host in `CreateRoom`
+#[derive(Debug, Deserialize, Serialize, Clone, InputObject)]
+pub struct RoomSettings {
+    pub max_players: u8,
+    pub rounds: u8,
+    /// Defaults to the application's `default_round_time_limit_secs`
+    pub round_time_limit_secs: Option<u32>,
+    /// Restrict prompts by difficulty, language, category or length
+    pub prompt_filter: Option<PromptFilter>,
+    /// Public when unset
+    pub access: Option<RoomAccess>,
+    /// Start as soon as every player is ready and has revealed their seed,
+    /// instead of waiting for `StartGame`; off when unset
+    pub auto_start: Option<bool>,
+}
+
 /// Operations that can be performed on the Type Arena contract
 #[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
 pub enum Operation {
     /// Create a new game room
     CreateRoom {
         room_id: CryptoHash,
-        max_players: u8,
-        rounds: u8,
+        settings: RoomSettings,
+        /// The host's `seed_commitment`, revealed at `StartGame`
+        seed_commitment: CryptoHash,
     },
     /// Join an existing room (sends message to host chain)
     JoinRoom {
         room_id: CryptoHash,
         host_chain_id: ChainId,
-        player_name: String,
+        /// Commitment to the secret this player reveals once the roster is locked
+        seed_commitment: CryptoHash,
+        /// Needed for private rooms unless the player is on the allowlist
+        passphrase: Option<String>,
+    },
+    /// Replace this player's seed commitment while the lobby is open. Needed after
+    /// a reopened lobby discarded the player's revealed secret.
+    CommitSeed {
+        room_id: CryptoHash,
+        host_chain_id: ChainId,
+        seed_commitment: CryptoHash,
+    },
+    /// Reveal the secret behind this player's seed commitment, once the roster is locked
+    RevealSeed {
+        room_id: CryptoHash,
+        host_chain_id: ChainId,
+        secret: Vec<u8>,
     },
-    /// Start the game (host only)
+    /// Mark this player ready in the lobby (sends to host if not on host chain). Once
+    /// every player, the host included, is ready the roster locks: joins and kicks are
+    /// refused and players reveal their seeds.
+    SetReady {
+        room_id: CryptoHash,
+        host_chain_id: ChainId,
+    },
+    /// Withdraw this player's ready mark, reopening a locked roster
+    SetNotReady {
+        room_id: CryptoHash,
+        host_chain_id: ChainId,
+    },
+    /// Start the game (host only) once the roster is locked, revealing the host's
+    /// seed secret. Every other player must have revealed their seed.
     StartGame {
         room_id: CryptoHash,
+        secret: Vec<u8>,
     },
-    /// Submit typing result for current round
+    /// Submit the encoded keystroke log for the current round; the host replays
+    /// it to recover the typed text and timing (see [`KeystrokeLog`])
     SubmitResult {
         room_id: CryptoHash,
         host_chain_id: ChainId,
-        wpm: u32,
-        accuracy: u32,
-        time_ms: u64,
+        keystrokes: Vec<u8>,
     },
-    /// Leave the room
+    /// Leave the room. Leaving a game in progress forfeits it, which counts as a loss.
     LeaveRoom {
         room_id: CryptoHash,
         host_chain_id: ChainId,
     },
+    /// Close a round whose time limit has passed (anyone can call)
+    CloseRound {
+        room_id: CryptoHash,
+        host_chain_id: ChainId,
+    },
+    /// Hand the room over to another player's chain (host only)
+    TransferHost {
+        room_id: CryptoHash,
+        new_host: AccountOwner,
+    },
+    /// Remove a player, who may rejoin after `KICK_COOLDOWN_SECS` (host only)
+    KickPlayer {
+        room_id: CryptoHash,
+        player: AccountOwner,
+    },
+    /// Remove a player for good (host only)
+    BanPlayer {
+        room_id: CryptoHash,
+        player: AccountOwner,
+    },
+    /// Take over a game in progress whose host chain has been silent for
+    /// `HOST_TIMEOUT_SECS` past the round deadline. Players of a stalled lobby leave instead.
+    ClaimHost {
+        room_id: CryptoHash,
+    },
+    /// Add a prompt to the library (admin only, on the creator chain)
+    AddPrompt {
+        text: String,
+        tags: PromptTags,
+    },
+    /// Stop drawing a prompt for new rounds (admin only, on the creator chain)
+    RetirePrompt {
+        prompt_id: u32,
+    },
+    /// Replace a prompt's tags (admin only, on the creator chain)
+    TagPrompt {
+        prompt_id: u32,
+        tags: PromptTags,
+    },
+    /// Add an owner to the prompt library admin allowlist (admin only)
+    AddAdmin {
+        owner: AccountOwner,
+    },
+    /// Remove an owner from the prompt library admin allowlist (admin only)
+    RemoveAdmin {
+        owner: AccountOwner,
+    },
+    /// Wait on the matchmaker chain for a room with similarly rated players. Refused
+    /// while this chain still has an unanswered request for the player.
+    EnqueueMatch {
+        preferences: MatchPreferences,
+        /// Commitment to the secret revealed once the formed room's roster is locked
+        seed_commitment: CryptoHash,
+    },
+    /// Stop waiting for a match
+    LeaveQueue,
+    /// Set the signer's profile, shown in rooms and leaderboards. The display
+    /// name must be the signer's registered name or the default placeholder.
+    SetProfile {
+        profile: Profile,
+    },
+    /// Register a unique display name on the registry (creator) chain, replacing
+    /// any name the signer already holds
+    ClaimName {
+        name: String,
+    },
+    /// Give up the signer's registered name
+    ReleaseName,
+    /// Hand the signer's registered name to another owner, who must not hold one
+    TransferName {
+        new_owner: AccountOwner,
+        new_owner_chain_id: ChainId,
+    },
+    /// Delete up to `limit` expired rooms stored on this chain (all when unset),
+    /// and stale directory entries on the directory chain. Anyone may call it.
+    CleanupRooms {
+        limit: Option<u32>,
+    },
 }
 
 /// Cross-chain messages for multiplayer synchronization
@@ -61,29 +243,48 @@ pub enum Message {
         room_id: CryptoHash,
         player: AccountOwner,
         player_chain_id: ChainId,
-        player_name: String,
+        profile: Profile,
+        seed_commitment: CryptoHash,
+        passphrase: Option<String>,
+    },
+    /// Player replaced their seed commitment
+    SeedCommitted {
+        room_id: CryptoHash,
+        player: AccountOwner,
+        seed_commitment: CryptoHash,
+    },
+    /// Player revealed their seed secret
+    SeedRevealed {
+        room_id: CryptoHash,
+        player: AccountOwner,
+        secret: Vec<u8>,
     },
-    /// Game started with prompt
+    /// Player toggled their ready mark
+    ReadyChanged {
+        room_id: CryptoHash,
+        player: AccountOwner,
+        ready: bool,
+    },
+    /// Game started with prompt, to be shown from `start_time` after the countdown
     GameStarted {
         room_id: CryptoHash,
         prompt: String,
         round: u8,
         start_time: Timestamp,
     },
-    /// Player submitted their result
+    /// Player submitted their encoded keystroke log
     ResultSubmitted {
         room_id: CryptoHash,
         player: AccountOwner,
-        wpm: u32,
-        accuracy: u32,
-        time_ms: u64,
+        keystrokes: Vec<u8>,
     },
-    /// Round ended, new round starting or game over
+    /// Round ended, new round starting at `start_time` or game over
     RoundEnded {
         room_id: CryptoHash,
         round: u8,
         results: Vec<PlayerResult>,
         next_prompt: Option<String>,
+        start_time: Timestamp,
     },
     /// Game ended with final standings
     GameEnded {
@@ -96,11 +297,254 @@ pub enum Message {
         room_id: CryptoHash,
         player: AccountOwner,
     },
+    /// The host removed the player; `until` is unset for a ban
+    PlayerRemoved {
+        room_id: CryptoHash,
+        player: AccountOwner,
+        until: Option<Timestamp>,
+    },
+    /// Request to close an expired round on the host chain
+    CloseRound {
+        room_id: CryptoHash,
+    },
+    /// Authoritative room state handed to the new host chain
+    HostTransferred {
+        room_id: CryptoHash,
+        state_bytes: Vec<u8>,
+    },
+    /// The room has a new host; `host_epoch` orders competing handovers
+    HostChanged {
+        room_id: CryptoHash,
+        host: AccountOwner,
+        host_chain_id: ChainId,
+        host_epoch: u32,
+    },
     /// Full state sync for new players (serialized room state)
     SyncState {
         room_id: CryptoHash,
         state_bytes: Vec<u8>,
     },
+    /// The host admitted the player into the room
+    JoinAccepted {
+        room_id: CryptoHash,
+    },
+    /// The host refused the join request
+    JoinRejected {
+        room_id: CryptoHash,
+        reason: TypeArenaError,
+    },
+    /// Ask the creator chain for the prompt library and future updates
+    SubscribePrompts,
+    /// New or changed prompts from the creator chain's library
+    PromptsUpdated {
+        prompts: Vec<Prompt>,
+    },
+    /// Player asks the matchmaker (creator) chain for a match
+    EnqueueMatch {
+        player: AccountOwner,
+        player_chain_id: ChainId,
+        profile: Profile,
+        seed_commitment: CryptoHash,
+        preferences: MatchPreferences,
+    },
+    /// Player no longer wants a match
+    LeaveQueue {
+        player: AccountOwner,
+    },
+    /// The matchmaker formed a room for the player; its state follows in `SyncState`
+    MatchFound {
+        player: AccountOwner,
+        room_id: CryptoHash,
+        host_chain_id: ChainId,
+    },
+    /// The matchmaker refused the queue request
+    QueueRejected {
+        player: AccountOwner,
+        reason: TypeArenaError,
+    },
+    /// Final standings of a matchmade game, reported to the matchmaker for rating
+    MatchFinished {
+        room_id: CryptoHash,
+        final_standings: Vec<PlayerResult>,
+        winner: AccountOwner,
+    },
+    /// A player changed their profile; sent to the hosts of their open rooms
+    ProfileUpdated {
+        owner: AccountOwner,
+        profile: Profile,
+    },
+    /// Player asks the registry (creator) chain for a name
+    ClaimName {
+        owner: AccountOwner,
+        owner_chain_id: ChainId,
+        name: String,
+    },
+    /// Player gives up their registered name
+    ReleaseName {
+        owner: AccountOwner,
+    },
+    /// Player hands their registered name to `new_owner`
+    TransferName {
+        owner: AccountOwner,
+        new_owner: AccountOwner,
+        new_owner_chain_id: ChainId,
+    },
+    /// The registry recorded `name` for `owner`
+    NameClaimed {
+        owner: AccountOwner,
+        name: String,
+    },
+    /// `owner` no longer holds a registered name
+    NameReleased {
+        owner: AccountOwner,
+    },
+    /// The registry refused a claim or transfer
+    NameRejected {
+        owner: AccountOwner,
+        reason: TypeArenaError,
+    },
+    /// Host lists or updates a lobby room on the directory (creator) chain
+    RegisterRoom {
+        listing: RoomListing,
+    },
+    /// Host removes a room that is no longer joinable from the directory
+    DeregisterRoom {
+        room_id: CryptoHash,
+        host_epoch: u32,
+    },
+    /// A request sent to the host chain was refused
+    Rejected {
+        room_id: CryptoHash,
+        error: TypeArenaError,
+    },
+}
+
+/// Errors returned by operations and sent back in `Message::Rejected`
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum TypeArenaError {
+    NotAuthenticated,
+    RoomNotFound,
+    RoomAlreadyExists,
+    RoomFull,
+    NotHost,
+    NotHostChain,
+    AlreadyHost,
+    HostStillActive,
+    NotInRoom,
+    AlreadyInRoom,
+    NotEnoughPlayers,
+    GameAlreadyStarted,
+    GameNotInProgress,
+    GameFinished,
+    InvalidPlayerCount,
+    InvalidRoundCount,
+    InvalidRoundTimeLimit,
+    AlreadySubmitted,
+    RoundNotStarted,
+    RoundExpired,
+    RoundNotExpired,
+    InvalidKeystrokeLog,
+    ImplausibleKeystrokes,
+    NotAdmin,
+    NotLibraryChain,
+    PromptNotFound,
+    InvalidPrompt,
+    LastAdmin,
+    NoMatchingPrompts,
+    LibraryNotSynced,
+    InvalidSeedSecret,
+    SeedMismatch,
+    SeedsNotRevealed,
+    SeedNotCommitted,
+    RosterLocked,
+    RosterNotLocked,
+    NotAllReady,
+    CannotRemoveHost,
+    Banned,
+    NotMatchmakerChain,
+    AlreadyQueued,
+    NotQueued,
+    InvalidRoomAccess,
+    NotInvited,
+    InvalidDisplayName,
+    InvalidCountry,
+    InvalidBio,
+    NameTaken,
+    NameNotRegistered,
+    ReservedName,
+    RecipientHasName,
+}
+
+impl std::fmt::Display for TypeArenaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::NotAuthenticated => "No authenticated signer",
+            Self::RoomNotFound => "Room not found",
+            Self::RoomAlreadyExists => "Room already exists",
+            Self::RoomFull => "Room is full",
+            Self::NotHost => "Only the host can do this",
+            Self::NotHostChain => "This chain is not hosting the room",
+            Self::AlreadyHost => "Already the host",
+            Self::HostStillActive => "Host is still active",
+            Self::NotInRoom => "Player is not in the room",
+            Self::AlreadyInRoom => "Player is already in the room",
+            Self::NotEnoughPlayers => "Need at least 2 players",
+            Self::GameAlreadyStarted => "Game already started",
+            Self::GameNotInProgress => "Game is not in progress",
+            Self::GameFinished => "Game already finished",
+            Self::InvalidPlayerCount => "Invalid player count",
+            Self::InvalidRoundCount => "Invalid round count",
+            Self::InvalidRoundTimeLimit => "Invalid round time limit (10-600s)",
+            Self::AlreadySubmitted => "Result already submitted this round",
+            Self::RoundNotStarted => "Round has not started yet",
+            Self::RoundExpired => "Round time limit has passed",
+            Self::RoundNotExpired => "Round time limit has not passed yet",
+            Self::InvalidKeystrokeLog => "Malformed keystroke log",
+            Self::ImplausibleKeystrokes => "Keystroke timing is not plausible",
+            Self::NotAdmin => "Only prompt library admins can do this",
+            Self::NotLibraryChain => "The prompt library is managed on the creator chain",
+            Self::PromptNotFound => "Prompt not found",
+            Self::InvalidPrompt => "Invalid prompt text",
+            Self::LastAdmin => "Cannot remove the last admin",
+            Self::NoMatchingPrompts => "No prompts match the room's prompt filter",
+            Self::LibraryNotSynced => "This chain has not received the prompt library yet",
+            Self::InvalidSeedSecret => "Seed secret must be 16 to 64 bytes",
+            Self::SeedMismatch => "Seed secret does not match the commitment",
+            Self::SeedsNotRevealed => "Not every player has revealed their seed",
+            Self::SeedNotCommitted => "Commit to a fresh seed secret first",
+            Self::RosterLocked => "The lobby is locked while players reveal their seeds",
+            Self::RosterNotLocked => "Seeds are revealed once every player is ready",
+            Self::NotAllReady => "Not every player is ready",
+            Self::CannotRemoveHost => "The host cannot remove themselves",
+            Self::Banned => "Removed from this room by the host",
+            Self::NotMatchmakerChain => "Matchmaking runs on the creator chain",
+            Self::AlreadyQueued => "Player is already waiting for a match",
+            Self::NotQueued => "Player is not waiting for a match",
+            Self::InvalidRoomAccess => "Private rooms need a passphrase or an allowlist",
+            Self::NotInvited => "This room is private",
+            Self::InvalidDisplayName => "Display name must be 3 to 24 letters, digits, spaces, '_', '-' or '.'",
+            Self::InvalidCountry => "Country must be a two-letter ISO 3166-1 code",
+            Self::InvalidBio => "Bio must be at most 160 characters without control characters",
+            Self::NameTaken => "Name is already registered to another player",
+            Self::NameNotRegistered => "Display name is not registered to this player",
+            Self::ReservedName => "Names resembling the default placeholder are reserved",
+            Self::RecipientHasName => "The recipient already holds a registered name",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for TypeArenaError {}
+
+impl From<KeystrokeLogError> for TypeArenaError {
+    fn from(error: KeystrokeLogError) -> Self {
+        match error {
+            KeystrokeLogError::NegativeOffset | KeystrokeLogError::ImplausiblyFast => {
+                Self::ImplausibleKeystrokes
+            }
+            _ => Self::InvalidKeystrokeLog,
+        }
+    }
 }
 
 /// Room status enum
@@ -111,6 +555,274 @@ pub enum RoomStatus {
     Finished,
 }
 
+/// Status of a join request or name claim as seen by the requesting chain
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum JoinStatus {
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+/// Prompt difficulty tier
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+/// Kind of text a prompt contains
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum PromptCategory {
+    Prose,
+    Quotes,
+    Code,
+    NumbersAndPunctuation,
+}
+
+/// Prompt length bucket
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum PromptLength {
+    Short,
+    Medium,
+    Long,
+}
+
+impl PromptLength {
+    pub fn of(text: &str) -> Self {
+        match text.chars().count() {
+            0..=59 => Self::Short,
+            60..=119 => Self::Medium,
+            _ => Self::Long,
+        }
+    }
+}
+
+/// Tags used to pick prompts for a room
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, SimpleObject, InputObject)]
+#[graphql(input_name = "PromptTagsInput")]
+pub struct PromptTags {
+    pub difficulty: Difficulty,
+    /// Language code, e.g. "en"
+    pub language: String,
+    pub category: PromptCategory,
+    pub length: PromptLength,
+}
+
+impl PromptTags {
+    /// Tags for untagged prose, such as the instantiation prompt set
+    pub fn default_for(text: &str) -> Self {
+        Self {
+            difficulty: Difficulty::Medium,
+            language: "en".to_string(),
+            category: PromptCategory::Prose,
+            length: PromptLength::of(text),
+        }
+    }
+}
+
+/// Prompt requirements chosen by the host; unset fields match any prompt
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, SimpleObject, InputObject)]
+#[graphql(input_name = "PromptFilterInput")]
+pub struct PromptFilter {
+    pub difficulty: Option<Difficulty>,
+    pub language: Option<String>,
+    pub category: Option<PromptCategory>,
+    pub length: Option<PromptLength>,
+}
+
+impl PromptFilter {
+    pub fn matches(&self, tags: &PromptTags) -> bool {
+        self.difficulty.is_none_or(|difficulty| difficulty == tags.difficulty)
+            && self.language.as_ref().is_none_or(|language| *language == tags.language)
+            && self.category.is_none_or(|category| category == tags.category)
+            && self.length.is_none_or(|length| length == tags.length)
+    }
+}
+
+/// Accepted display name length, in characters
+pub const MIN_DISPLAY_NAME_CHARS: usize = 3;
+pub const MAX_DISPLAY_NAME_CHARS: usize = 24;
+
+/// Longest profile bio, in characters
+pub const MAX_BIO_CHARS: usize = 160;
+
+/// A player's public profile
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, SimpleObject, InputObject)]
+#[graphql(input_name = "ProfileInput")]
+pub struct Profile {
+    pub display_name: String,
+    /// Hash of the avatar image, e.g. a data blob hash
+    pub avatar_hash: Option<CryptoHash>,
+    /// ISO 3166-1 alpha-2 code, such as "DE"
+    pub country: Option<String>,
+    pub bio: String,
+}
+
+impl Profile {
+    /// Profile used until the player sets one
+    pub fn default_for(owner: &AccountOwner) -> Self {
+        Self {
+            display_name: format!("Player_{}", &owner.to_string()[..8]),
+            avatar_hash: None,
+            country: None,
+            bio: String::new(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), TypeArenaError> {
+        if !is_valid_display_name(&self.display_name) {
+            return Err(TypeArenaError::InvalidDisplayName);
+        }
+        if let Some(country) = &self.country {
+            if country.len() != 2 || !country.chars().all(|c| c.is_ascii_uppercase()) {
+                return Err(TypeArenaError::InvalidCountry);
+            }
+        }
+        if self.bio.chars().count() > MAX_BIO_CHARS || self.bio.chars().any(char::is_control) {
+            return Err(TypeArenaError::InvalidBio);
+        }
+        Ok(())
+    }
+}
+
+/// Length and charset rules for display names: ASCII letters and digits with
+/// single inner spaces, '_', '-' or '.'
+pub fn is_valid_display_name(name: &str) -> bool {
+    let name_chars = name.chars().count();
+    (MIN_DISPLAY_NAME_CHARS..=MAX_DISPLAY_NAME_CHARS).contains(&name_chars)
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '_' | '-' | '.'))
+        && !name.starts_with(' ')
+        && !name.ends_with(' ')
+        && !name.contains("  ")
+}
+
+/// Registry key for a display name. Case is folded, separators are dropped and
+/// characters that look alike are mapped to one representative, so "Speedy",
+/// "sp33dy" and "S.P.E.E.D.Y" all collide. Display names are ASCII-only, which
+/// keeps non-Latin look-alikes out entirely.
+pub fn normalize_name(name: &str) -> String {
+    let folded: String = name
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-' | '.'))
+        .map(|c| match c.to_ascii_lowercase() {
+            '0' => 'o',
+            '1' | 'i' => 'l',
+            '2' => 'z',
+            '3' => 'e',
+            '4' => 'a',
+            '5' => 's',
+            '6' => 'g',
+            '7' => 't',
+            '8' => 'b',
+            '9' => 'q',
+            c => c,
+        })
+        .collect();
+    folded.replace("rn", "m").replace("vv", "w")
+}
+
+/// Whether a name could pass for the `Player_0x...` placeholder of `Profile::default_for`
+pub fn is_reserved_name(name: &str) -> bool {
+    normalize_name(name).starts_with(&normalize_name("Player_0x"))
+}
+
+/// Whether a room shows up in room listings
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum RoomVisibility {
+    Public,
+    /// Hidden from listings; joining needs the passphrase or an allowlist entry
+    Private,
+}
+
+/// Who may join a room
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, SimpleObject, InputObject)]
+#[graphql(input_name = "RoomAccessInput")]
+pub struct RoomAccess {
+    pub visibility: RoomVisibility,
+    /// `passphrase_hash` of the room's passphrase
+    pub passphrase_hash: Option<CryptoHash>,
+    /// Owners admitted without the passphrase
+    pub allowlist: Vec<AccountOwner>,
+}
+
+impl Default for RoomAccess {
+    fn default() -> Self {
+        Self {
+            visibility: RoomVisibility::Public,
+            passphrase_hash: None,
+            allowlist: Vec::new(),
+        }
+    }
+}
+
+impl RoomAccess {
+    pub fn is_private(&self) -> bool {
+        self.visibility == RoomVisibility::Private
+    }
+
+    /// Private rooms must give invitees some way in
+    pub fn is_valid(&self) -> bool {
+        !self.is_private() || self.passphrase_hash.is_some() || !self.allowlist.is_empty()
+    }
+
+    pub fn admits(&self, room_id: CryptoHash, player: &AccountOwner, passphrase: Option<&str>) -> bool {
+        if !self.is_private() || self.allowlist.contains(player) {
+            return true;
+        }
+        match (self.passphrase_hash, passphrase) {
+            (Some(expected), Some(passphrase)) => passphrase_hash(room_id, passphrase) == expected,
+            _ => false,
+        }
+    }
+}
+
+/// Summary of a lobby room kept by the directory chain
+#[derive(Debug, Deserialize, Serialize, Clone, SimpleObject)]
+pub struct RoomListing {
+    pub room_id: CryptoHash,
+    pub host: AccountOwner,
+    pub host_chain_id: ChainId,
+    pub host_epoch: u32,
+    pub player_count: u8,
+    pub max_players: u8,
+    pub total_rounds: u8,
+    pub round_time_limit_secs: u32,
+    pub prompt_filter: PromptFilter,
+    pub auto_start: bool,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+/// What a player is willing to be matched into
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, SimpleObject, InputObject)]
+#[graphql(input_name = "MatchPreferencesInput")]
+pub struct MatchPreferences {
+    pub player_count: u8,
+    pub rounds: u8,
+    /// Any category when unset
+    pub category: Option<PromptCategory>,
+}
+
+/// Largest rating gap between the players of a match formed right away
+pub const MATCH_RATING_SPREAD: f64 = 200.0;
+
+/// How much the allowed rating gap widens per second the oldest player has waited
+pub const MATCH_SPREAD_GROWTH_PER_SEC: f64 = 5.0;
+
+/// Longest prompt the library accepts, in characters
+pub const MAX_PROMPT_CHARS: usize = 500;
+
+/// A prompt in the on-chain library
+#[derive(Debug, Deserialize, Serialize, Clone, SimpleObject)]
+pub struct Prompt {
+    pub id: u32,
+    pub text: String,
+    pub tags: PromptTags,
+    /// Retired prompts are kept for history but never drawn
+    pub retired: bool,
+}
+
 /// Player result for a round or game
 #[derive(Debug, Deserialize, Serialize, Clone, SimpleObject, InputObject)]
 #[graphql(input_name = "PlayerResultInput")]
@@ -121,18 +833,392 @@ pub struct PlayerResult {
     pub accuracy: u32,
     pub time_ms: u64,
     pub score: u32,
+    /// Did not finish before the round time limit
+    pub dnf: bool,
 }
 
 impl PlayerResult {
-    pub fn calculate_score(wpm: u32, accuracy: u32) -> u32 {
-        // Score = WPM * (accuracy/100)^2
-        // This heavily rewards accuracy while still valuing speed
-        let accuracy_factor = (accuracy as f64 / 100.0).powi(2);
+    /// Net WPM: only correctly typed characters count, five characters per word
+    pub fn calculate_wpm(correct_chars: u32, time_ms: u64) -> u32 {
+        if time_ms == 0 {
+            return 0;
+        }
+        (correct_chars as u64 * 12_000 / time_ms) as u32
+    }
+
+    /// Percentage of the longer of prompt and typed text that lines up with the other.
+    /// Skipped, extra and wrong characters each count as one error.
+    pub fn calculate_accuracy(correct_chars: u32, prompt: &str, typed_text: &str) -> u32 {
+        let total = prompt.chars().count().max(typed_text.chars().count());
+        if total == 0 {
+            return 0;
+        }
+        (correct_chars as u64 * 100 / total as u64) as u32
+    }
+
+    /// Number of characters typed correctly, found by aligning the typed text with the
+    /// prompt at the smallest edit distance. A skipped or extra character costs one
+    /// error instead of shifting every character after it.
+    pub fn count_correct_chars(prompt: &str, typed_text: &str) -> u32 {
+        let prompt: Vec<char> = prompt.chars().collect();
+        // (edit distance, matched characters) between prefixes, preferring the
+        // alignment with more matches among those at the same distance
+        let best = |a: (u32, u32), b: (u32, u32)| if (a.0, Reverse(a.1)) <= (b.0, Reverse(b.1)) { a } else { b };
+        let mut previous: Vec<(u32, u32)> = (0..=prompt.len() as u32).map(|skipped| (skipped, 0)).collect();
+        for (row, typed) in typed_text.chars().enumerate() {
+            let mut current = Vec::with_capacity(prompt.len() + 1);
+            current.push((row as u32 + 1, 0));
+            for (column, expected) in prompt.iter().enumerate() {
+                let (distance, matches) = previous[column];
+                let aligned = if *expected == typed { (distance, matches + 1) } else { (distance + 1, matches) };
+                let skipped = (current[column].0 + 1, current[column].1);
+                let extra = (previous[column + 1].0 + 1, previous[column + 1].1);
+                current.push(best(best(aligned, skipped), extra));
+            }
+            previous = current;
+        }
+        previous[prompt.len()].1
+    }
+
+    pub fn calculate_score(wpm: u32, accuracy: u32, formula: ScoringFormula) -> u32 {
+        let accuracy_factor = match formula {
+            // Score = WPM * (accuracy/100)^2
+            // This heavily rewards accuracy while still valuing speed
+            ScoringFormula::AccuracySquared => (accuracy as f64 / 100.0).powi(2),
+            ScoringFormula::AccuracyLinear => accuracy as f64 / 100.0,
+        };
         (wpm as f64 * accuracy_factor) as u32
     }
 }
 
-/// Word bank for typing challenges
+/// Glicko-2 conversion factor between the public rating scale and the internal one
+const GLICKO_SCALE: f64 = 173.7178;
+
+/// Glicko-2 system constant limiting how fast volatility changes
+const GLICKO_TAU: f64 = 0.5;
+
+/// Convergence tolerance and iteration cap for the volatility search
+const GLICKO_EPSILON: f64 = 0.000_001;
+const GLICKO_MAX_ITERATIONS: u32 = 100;
+
+/// Glicko-2 skill rating. New players start at 1500 ± 350.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, SimpleObject)]
+pub struct Rating {
+    pub rating: f64,
+    /// Uncertainty of `rating`; shrinks as the player plays more games
+    pub deviation: f64,
+    /// Expected fluctuation of the player's performance
+    pub volatility: f64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: 1500.0,
+            deviation: 350.0,
+            volatility: 0.06,
+        }
+    }
+}
+
+impl Rating {
+    /// Rating after one rating period against `outcomes`: each opponent's
+    /// rating paired with this player's score against them (1 win, 0.5 draw, 0 loss)
+    pub fn updated(&self, outcomes: &[(Rating, f64)]) -> Rating {
+        if outcomes.is_empty() {
+            return *self;
+        }
+        let mu = (self.rating - 1500.0) / GLICKO_SCALE;
+        let phi = self.deviation / GLICKO_SCALE;
+
+        let g = |phi: f64| 1.0 / (1.0 + 3.0 * phi * phi / (std::f64::consts::PI * std::f64::consts::PI)).sqrt();
+        let mut inverse_variance = 0.0;
+        let mut improvement = 0.0;
+        for (opponent, score) in outcomes {
+            let opponent_mu = (opponent.rating - 1500.0) / GLICKO_SCALE;
+            let opponent_g = g(opponent.deviation / GLICKO_SCALE);
+            let expected = 1.0 / (1.0 + (-opponent_g * (mu - opponent_mu)).exp());
+            inverse_variance += opponent_g * opponent_g * expected * (1.0 - expected);
+            improvement += opponent_g * (score - expected);
+        }
+        let variance = 1.0 / inverse_variance;
+        let delta = variance * improvement;
+
+        let volatility = Self::updated_volatility(self.volatility, phi, variance, delta);
+        let pre_period_phi = (phi * phi + volatility * volatility).sqrt();
+        let new_phi = 1.0 / (1.0 / (pre_period_phi * pre_period_phi) + 1.0 / variance).sqrt();
+        let new_mu = mu + new_phi * new_phi * improvement;
+
+        Rating {
+            rating: new_mu * GLICKO_SCALE + 1500.0,
+            deviation: (new_phi * GLICKO_SCALE).min(Rating::default().deviation),
+            volatility,
+        }
+    }
+
+    /// Solve for the new volatility with the Illinois method (Glicko-2 step 5)
+    fn updated_volatility(sigma: f64, phi: f64, variance: f64, delta: f64) -> f64 {
+        let a = (sigma * sigma).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            let denominator = phi * phi + variance + ex;
+            ex * (delta * delta - phi * phi - variance - ex) / (2.0 * denominator * denominator)
+                - (x - a) / (GLICKO_TAU * GLICKO_TAU)
+        };
+
+        let mut lower = a;
+        let mut upper = if delta * delta > phi * phi + variance {
+            (delta * delta - phi * phi - variance).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * GLICKO_TAU) < 0.0 && k < GLICKO_MAX_ITERATIONS as f64 {
+                k += 1.0;
+            }
+            a - k * GLICKO_TAU
+        };
+
+        let mut f_lower = f(lower);
+        let mut f_upper = f(upper);
+        for _ in 0..GLICKO_MAX_ITERATIONS {
+            if (upper - lower).abs() <= GLICKO_EPSILON {
+                break;
+            }
+            let candidate = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+            let f_candidate = f(candidate);
+            if f_candidate * f_upper <= 0.0 {
+                lower = upper;
+                f_lower = f_upper;
+            } else {
+                f_lower /= 2.0;
+            }
+            upper = candidate;
+            f_upper = f_candidate;
+        }
+        (lower / 2.0).exp()
+    }
+}
+
+/// How long the host chain may stay silent past the current round's deadline
+/// before players can claim a game in progress
+pub const HOST_TIMEOUT_SECS: u64 = 300;
+
+/// How long a kicked player must wait before rejoining the room
+pub const KICK_COOLDOWN_SECS: u64 = 300;
+
+/// Accepted length range of a seed secret, in bytes
+pub const MIN_SEED_SECRET_BYTES: usize = 16;
+pub const MAX_SEED_SECRET_BYTES: usize = 64;
+
+/// What a seed commitment hashes: the secret bound to its owner, so a commitment
+/// cannot be copied by another player
+#[derive(Serialize, Deserialize)]
+struct SeedPreimage {
+    owner: AccountOwner,
+    secret: Vec<u8>,
+}
+
+impl BcsHashable<'_> for SeedPreimage {}
+
+/// Commitment to publish in `CreateRoom`/`JoinRoom` for a secret revealed later.
+/// The secrets of all players together seed prompt selection for the game.
+pub fn seed_commitment(owner: AccountOwner, secret: &[u8]) -> CryptoHash {
+    CryptoHash::new(&SeedPreimage {
+        owner,
+        secret: secret.to_vec(),
+    })
+}
+
+/// What a room passphrase hash covers: the passphrase bound to its room, so the
+/// same passphrase hashes differently in every room
+#[derive(Serialize, Deserialize)]
+struct PassphrasePreimage {
+    room_id: CryptoHash,
+    passphrase: String,
+}
+
+impl BcsHashable<'_> for PassphrasePreimage {}
+
+/// Hash to set in a private room's `RoomAccess`. Joining sends the passphrase
+/// itself, which is then visible on the host chain, so treat it as an invite
+/// code and use the allowlist when that matters.
+pub fn passphrase_hash(room_id: CryptoHash, passphrase: &str) -> CryptoHash {
+    CryptoHash::new(&PassphrasePreimage {
+        room_id,
+        passphrase: passphrase.to_string(),
+    })
+}
+
+/// Current version byte of the keystroke log encoding
+pub const KEYSTROKE_LOG_VERSION: u8 = 1;
+
+/// Upper bound on events in a single log, to keep replay cost bounded
+pub const MAX_KEYSTROKES: usize = 4096;
+
+/// Minimum plausible average gap between keystrokes over a whole log
+pub const MIN_AVERAGE_KEY_INTERVAL_MS: i64 = 10;
+
+/// Share of gaps, in percent, below `MIN_AVERAGE_KEY_INTERVAL_MS` at which a log is
+/// rejected, so bursts padded out with long pauses do not pass on their average
+pub const MAX_FAST_GAP_PERCENT: usize = 90;
+
+/// A single key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Backspace,
+}
+
+/// A key press with its offset in milliseconds from the round start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keystroke {
+    pub offset_ms: i64,
+    pub key: Key,
+}
+
+/// Reasons a keystroke log is rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeystrokeLogError {
+    UnsupportedVersion,
+    Truncated,
+    InvalidKey,
+    Empty,
+    TooManyKeystrokes,
+    NegativeOffset,
+    OffsetOverflow,
+    ImplausiblyFast,
+}
+
+/// Text and timing rebuilt by replaying a keystroke log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    pub text: String,
+    pub time_ms: u64,
+}
+
+/// Keystroke log submitted with a result.
+///
+/// Binary encoding (version 1):
+///
+/// ```text
+/// log   := version:u8 event*
+/// event := delta:varint(zigzag i64) key:varint(u32)
+/// ```
+///
+/// Varints are unsigned LEB128. `delta` is the signed number of milliseconds
+/// since the previous event, or since `round_start_time` for the first event.
+/// `key` is 0 for a backspace, otherwise the Unicode scalar value plus one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeystrokeLog {
+    pub keystrokes: Vec<Keystroke>,
+}
+
+impl KeystrokeLog {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![KEYSTROKE_LOG_VERSION];
+        let mut previous = 0i64;
+        for keystroke in &self.keystrokes {
+            let delta = keystroke.offset_ms.wrapping_sub(previous);
+            write_varint(&mut bytes, ((delta << 1) ^ (delta >> 63)) as u64);
+            let key = match keystroke.key {
+                Key::Backspace => 0,
+                Key::Char(c) => c as u64 + 1,
+            };
+            write_varint(&mut bytes, key);
+            previous = keystroke.offset_ms;
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, KeystrokeLogError> {
+        let (&version, mut rest) = bytes.split_first().ok_or(KeystrokeLogError::Truncated)?;
+        if version != KEYSTROKE_LOG_VERSION {
+            return Err(KeystrokeLogError::UnsupportedVersion);
+        }
+
+        let mut keystrokes = Vec::new();
+        let mut offset = 0i64;
+        while !rest.is_empty() {
+            if keystrokes.len() >= MAX_KEYSTROKES {
+                return Err(KeystrokeLogError::TooManyKeystrokes);
+            }
+            let zigzag = read_varint(&mut rest)?;
+            let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+            offset = offset.checked_add(delta).ok_or(KeystrokeLogError::OffsetOverflow)?;
+            let key = match read_varint(&mut rest)? {
+                0 => Key::Backspace,
+                code => u32::try_from(code - 1)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map(Key::Char)
+                    .ok_or(KeystrokeLogError::InvalidKey)?,
+            };
+            keystrokes.push(Keystroke { offset_ms: offset, key });
+        }
+        Ok(Self { keystrokes })
+    }
+
+    /// Replay the log into the final text and elapsed time, rejecting logs
+    /// that go back in time or are typed faster than humanly possible, on
+    /// average or in nearly every gap
+    pub fn replay(&self) -> Result<Replay, KeystrokeLogError> {
+        let first = self.keystrokes.first().ok_or(KeystrokeLogError::Empty)?;
+        let last = self.keystrokes.last().ok_or(KeystrokeLogError::Empty)?;
+
+        let mut previous = 0i64;
+        let mut text = String::new();
+        for keystroke in &self.keystrokes {
+            if keystroke.offset_ms < previous {
+                return Err(KeystrokeLogError::NegativeOffset);
+            }
+            previous = keystroke.offset_ms;
+            match keystroke.key {
+                Key::Char(c) => text.push(c),
+                Key::Backspace => {
+                    text.pop();
+                }
+            }
+        }
+
+        let gaps = self.keystrokes.len() - 1;
+        let fast_gaps = self.keystrokes.windows(2)
+            .filter(|pair| pair[1].offset_ms - pair[0].offset_ms < MIN_AVERAGE_KEY_INTERVAL_MS)
+            .count();
+        if gaps > 0 {
+            let average_gap = (last.offset_ms - first.offset_ms) / gaps as i64;
+            if average_gap < MIN_AVERAGE_KEY_INTERVAL_MS || fast_gaps * 100 >= gaps * MAX_FAST_GAP_PERCENT {
+                return Err(KeystrokeLogError::ImplausiblyFast);
+            }
+        }
+
+        Ok(Replay {
+            text,
+            time_ms: last.offset_ms as u64,
+        })
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, KeystrokeLogError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first().ok_or(KeystrokeLogError::Truncated)?;
+        *bytes = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(KeystrokeLogError::Truncated)
+}
+
+/// Built-in prompts, used to seed the library
 pub const WORD_BANK: &[&str] = &[
     "The quick brown fox jumps over the lazy dog near the riverbank.",
     "Blockchain technology enables decentralized applications with trustless consensus.",
@@ -150,3 +1236,198 @@ pub const WORD_BANK: &[&str] = &[
     "The clock is ticking and every word matters in this typing race.",
     "Precision typing separates the good players from the great ones.",
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(keystrokes: &[(i64, Key)]) -> KeystrokeLog {
+        KeystrokeLog {
+            keystrokes: keystrokes
+                .iter()
+                .map(|&(offset_ms, key)| Keystroke { offset_ms, key })
+                .collect(),
+        }
+    }
+
+    fn encoded(events: &[(u64, u64)]) -> Vec<u8> {
+        let mut bytes = vec![KEYSTROKE_LOG_VERSION];
+        for &(delta, key) in events {
+            write_varint(&mut bytes, delta);
+            write_varint(&mut bytes, key);
+        }
+        bytes
+    }
+
+    #[test]
+    fn keystroke_log_round_trips() {
+        let original = log(&[
+            (120, Key::Char('h')),
+            (250, Key::Char('é')),
+            (400, Key::Backspace),
+            (520, Key::Char('\u{1F600}')),
+            (100_000, Key::Char('i')),
+        ]);
+        assert_eq!(KeystrokeLog::decode(&original.encode()), Ok(original));
+    }
+
+    #[test]
+    fn keystroke_log_encodes_negative_deltas_with_zigzag() {
+        let original = log(&[(-5, Key::Char('a')), (300, Key::Char('b')), (200, Key::Char('c'))]);
+        let bytes = original.encode();
+        // -5 zigzags to 9, and 300 - (-5) = 305 to 610, a two-byte varint
+        assert_eq!(&bytes[..6], &[KEYSTROKE_LOG_VERSION, 9, b'a' + 1, 0xe2, 0x04, b'b' + 1]);
+        // 200 - 300 = -100 zigzags to 199
+        assert_eq!(&bytes[6..], &[0xc7, 0x01, b'c' + 1]);
+        assert_eq!(KeystrokeLog::decode(&bytes), Ok(original));
+    }
+
+    #[test]
+    fn keystroke_log_rejects_truncated_input() {
+        assert_eq!(KeystrokeLog::decode(&[]), Err(KeystrokeLogError::Truncated));
+        // Varint continuation bit set on the last byte
+        assert_eq!(KeystrokeLog::decode(&[KEYSTROKE_LOG_VERSION, 0x80]), Err(KeystrokeLogError::Truncated));
+        assert_eq!(KeystrokeLog::decode(&[KEYSTROKE_LOG_VERSION, 2, 0x80]), Err(KeystrokeLogError::Truncated));
+        // Delta without a key
+        assert_eq!(KeystrokeLog::decode(&[KEYSTROKE_LOG_VERSION, 2]), Err(KeystrokeLogError::Truncated));
+        // A varint running past ten bytes
+        let mut overlong = vec![KEYSTROKE_LOG_VERSION];
+        overlong.extend([0xff; 10]);
+        overlong.push(0x01);
+        assert_eq!(KeystrokeLog::decode(&overlong), Err(KeystrokeLogError::Truncated));
+    }
+
+    #[test]
+    fn keystroke_log_rejects_overflowing_offsets() {
+        // Each delta zigzags to i64::MAX, so the second one overflows the offset
+        let overflowing = encoded(&[(u64::MAX - 1, b'a' as u64 + 1), (u64::MAX - 1, b'b' as u64 + 1)]);
+        assert_eq!(KeystrokeLog::decode(&overflowing), Err(KeystrokeLogError::OffsetOverflow));
+        assert_eq!(
+            TypeArenaError::from(KeystrokeLogError::OffsetOverflow),
+            TypeArenaError::InvalidKeystrokeLog
+        );
+    }
+
+    #[test]
+    fn keystroke_log_rejects_invalid_keys() {
+        // A surrogate is not a Unicode scalar value
+        assert_eq!(KeystrokeLog::decode(&encoded(&[(2, 0xd800 + 1)])), Err(KeystrokeLogError::InvalidKey));
+        assert_eq!(KeystrokeLog::decode(&encoded(&[(2, 0x11_0000 + 1)])), Err(KeystrokeLogError::InvalidKey));
+        assert_eq!(KeystrokeLog::decode(&encoded(&[(2, u32::MAX as u64 + 2)])), Err(KeystrokeLogError::InvalidKey));
+        assert_eq!(KeystrokeLog::decode(&[2, 2, 0x62]), Err(KeystrokeLogError::UnsupportedVersion));
+    }
+
+    #[test]
+    fn keystroke_log_caps_events() {
+        let at_cap = encoded(&vec![(40, b'a' as u64 + 1); MAX_KEYSTROKES]);
+        assert_eq!(KeystrokeLog::decode(&at_cap).map(|log| log.keystrokes.len()), Ok(MAX_KEYSTROKES));
+
+        let over_cap = encoded(&vec![(40, b'a' as u64 + 1); MAX_KEYSTROKES + 1]);
+        assert_eq!(KeystrokeLog::decode(&over_cap), Err(KeystrokeLogError::TooManyKeystrokes));
+    }
+
+    #[test]
+    fn replay_rebuilds_text_and_time() {
+        let typed = log(&[
+            (100, Key::Char('c')),
+            (200, Key::Char('x')),
+            (300, Key::Backspace),
+            (400, Key::Char('a')),
+            (500, Key::Char('t')),
+        ]);
+        let replay = typed.replay().unwrap();
+        assert_eq!(replay, Replay { text: "cat".to_string(), time_ms: 500 });
+        assert_eq!(KeystrokeLog::default().replay(), Err(KeystrokeLogError::Empty));
+    }
+
+    #[test]
+    fn replay_rejects_backwards_offsets() {
+        let backwards = log(&[(100, Key::Char('a')), (300, Key::Char('b')), (250, Key::Char('c'))]);
+        assert_eq!(backwards.replay(), Err(KeystrokeLogError::NegativeOffset));
+
+        let before_start = log(&[(-1, Key::Char('a')), (100, Key::Char('b'))]);
+        assert_eq!(before_start.replay(), Err(KeystrokeLogError::NegativeOffset));
+    }
+
+    #[test]
+    fn replay_rejects_implausibly_fast_typing() {
+        // 29ms over three gaps averages just under the minimum
+        let fast = log(&[(1000, Key::Char('a')), (1010, Key::Char('b')), (1020, Key::Char('c')), (1029, Key::Char('d'))]);
+        assert_eq!(fast.replay(), Err(KeystrokeLogError::ImplausiblyFast));
+
+        let steady = log(&[(1000, Key::Char('a')), (1010, Key::Char('b')), (1020, Key::Char('c')), (1030, Key::Char('d'))]);
+        assert_eq!(steady.replay().map(|replay| replay.text), Ok("abcd".to_string()));
+    }
+
+    #[test]
+    fn replay_rejects_bursts_padded_with_pauses() {
+        // Twenty keys 2ms apart, then a pause that lifts the average well over the minimum
+        let mut burst: Vec<(i64, Key)> = (0..20).map(|i| (1000 + 2 * i, Key::Char('a'))).collect();
+        burst.push((5000, Key::Char('b')));
+        assert_eq!(log(&burst).replay(), Err(KeystrokeLogError::ImplausiblyFast));
+
+        // A human roll with a few quick gaps among ordinary ones is fine
+        let mut rolled: Vec<(i64, Key)> = (0..20).map(|i| (1000 + 80 * i, Key::Char('a'))).collect();
+        rolled.extend([(2525, Key::Char('b')), (2530, Key::Char('c'))]);
+        assert!(log(&rolled).replay().is_ok());
+    }
+
+    #[test]
+    fn look_alike_names_normalize_to_one_key() {
+        let key = normalize_name("Speedy");
+        for name in ["speedy", "SPEEDY", "sp33dy", "S.P.E.E.D.Y", "s_p-e e.d_y", "5peedy"] {
+            assert_eq!(normalize_name(name), key, "{name}");
+        }
+        assert_eq!(normalize_name("Bill"), normalize_name("B1ll"));
+        assert_eq!(normalize_name("Bill"), normalize_name("BiII"));
+        assert_eq!(normalize_name("corn"), normalize_name("com"));
+        assert_eq!(normalize_name("vvolf"), normalize_name("Wolf"));
+        assert_ne!(normalize_name("Speedy"), normalize_name("Speedo"));
+    }
+
+    #[test]
+    fn names_passing_for_the_placeholder_are_reserved() {
+        for name in ["Player_0x1a2b3c", "player 0x", "PLAYER.OX99", "P1ayer_0x", "Pl4yer-Ox"] {
+            assert!(is_reserved_name(name), "{name}");
+        }
+        for name in ["Player One", "Player", "Play3r", "0x Player", "Speedy"] {
+            assert!(!is_reserved_name(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn skipped_and_extra_characters_cost_one_error_each() {
+        let prompt = "The quick brown fox";
+        assert_eq!(PlayerResult::count_correct_chars(prompt, prompt), 19);
+
+        let skipped = "Th quick brown fox";
+        let correct = PlayerResult::count_correct_chars(prompt, skipped);
+        assert_eq!(correct, 18);
+        assert_eq!(PlayerResult::calculate_accuracy(correct, prompt, skipped), 94);
+
+        let extra = "Thee quick brown fox";
+        let correct = PlayerResult::count_correct_chars(prompt, extra);
+        assert_eq!(correct, 19);
+        assert_eq!(PlayerResult::calculate_accuracy(correct, prompt, extra), 95);
+
+        assert_eq!(PlayerResult::count_correct_chars(prompt, "Tha quick brown fix"), 17);
+        assert_eq!(PlayerResult::count_correct_chars(prompt, "The quick"), 9);
+        assert_eq!(PlayerResult::count_correct_chars(prompt, ""), 0);
+        assert_eq!(PlayerResult::count_correct_chars("", "typed"), 0);
+    }
+
+    #[test]
+    fn rating_update_matches_glickmans_worked_example() {
+        let rating = |rating, deviation| Rating { rating, deviation, volatility: 0.06 };
+        let player = rating(1500.0, 200.0);
+        let updated = player.updated(&[
+            (rating(1400.0, 30.0), 1.0),
+            (rating(1550.0, 100.0), 0.0),
+            (rating(1700.0, 300.0), 0.0),
+        ]);
+
+        assert!((updated.rating - 1464.05).abs() < 0.01, "rating {}", updated.rating);
+        assert!((updated.deviation - 151.52).abs() < 0.01, "deviation {}", updated.deviation);
+        assert!((updated.volatility - 0.05999).abs() < 0.00001, "volatility {}", updated.volatility);
+    }
+}
@@ -1,21 +1,32 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
+// Room helpers in `state` are shared with the contract and partly unused here
+#[allow(dead_code)]
 mod state;
 
-use std::sync::Arc;
-use async_graphql::{EmptyMutation, EmptySubscription, Object, Request, Response, Schema, SimpleObject};
+use std::{fmt, sync::Arc};
+use async_graphql::{
+    connection::{query, Connection, CursorType, Edge},
+    EmptyMutation, EmptySubscription, InputObject, Object, Request, Response, Schema, SimpleObject,
+};
 use linera_sdk::{
     abi::WithServiceAbi,
-    linera_base_types::{AccountOwner, CryptoHash},
-    views::View,
+    linera_base_types::{AccountOwner, CryptoHash, Timestamp},
+    views::{CustomSerialize, CustomSetView, View},
     Service, ServiceRuntime,
 };
-use state::{PlayerStats, TypeArenaState};
-use type_arena::{RoomStatus, TypeArenaAbi};
+use state::{
+    GameRoom, MatchRecord, NameClaim, PlayerRank, PlayerStats, QueueEntry, RatingChange,
+    RegisteredName, RoomBan, RoomOrder, RoundRecord, TypeArenaState,
+};
+use type_arena::{
+    normalize_name, passphrase_hash, seed_commitment, JoinStatus, MatchPreferences, Profile,
+    Prompt, PromptCategory, PromptFilter, RoomListing, RoomStatus, RoomVisibility, TypeArenaAbi,
+    TypeArenaError, TypeArenaParameters,
+};
 
 pub struct TypeArenaService {
-    state: TypeArenaState,
-    #[allow(dead_code)]
+    state: Arc<TypeArenaState>,
     runtime: Arc<ServiceRuntime<Self>>,
 }
 
@@ -26,67 +37,25 @@ impl WithServiceAbi for TypeArenaService {
 }
 
 impl Service for TypeArenaService {
-    type Parameters = ();
+    type Parameters = TypeArenaParameters;
 
     async fn new(runtime: ServiceRuntime<Self>) -> Self {
         let state = TypeArenaState::load(runtime.root_view_storage_context())
             .await
             .expect("Failed to load state");
         TypeArenaService { 
-            state, 
+            state: Arc::new(state), 
             runtime: Arc::new(runtime),
         }
     }
 
     async fn handle_query(&self, request: Request) -> Response {
-        // Extract room data for the query
-        let mut rooms = Vec::new();
-        let _ = self.state.rooms.for_each_index_value(|_id, room| {
-            let r = room.into_owned();
-            rooms.push(RoomInfo {
-                room_id: r.room_id.to_string(),
-                host: r.host.to_string(),
-                player_count: r.players.len() as u32,
-                max_players: r.max_players,
-                status: format!("{:?}", r.status),
-                current_round: r.current_round,
-                total_rounds: r.total_rounds,
-                current_prompt: r.current_prompt.clone(),
-                players: r.players.iter().map(|p| PlayerInfo {
-                    owner: p.owner.to_string(),
-                    display_name: p.display_name.clone(),
-                    is_ready: p.is_ready,
-                }).collect(),
-                is_joinable: r.status == RoomStatus::Lobby && r.players.len() < r.max_players as usize,
-            });
-            Ok(())
-        }).await;
-        
-        // Extract player stats for leaderboard
-        let mut leaderboard = Vec::new();
-        let _ = self.state.player_stats.for_each_index_value(|owner, stats| {
-            let s = stats.into_owned();
-            if s.games_played > 0 {
-                leaderboard.push(LeaderboardEntry {
-                    player: owner.to_string(),
-                    games_won: s.games_won,
-                    games_played: s.games_played,
-                    average_wpm: if s.rounds_played > 0 { (s.total_wpm / s.rounds_played as u64) as u32 } else { 0 },
-                    average_accuracy: if s.rounds_played > 0 { (s.total_accuracy / s.rounds_played as u64) as u32 } else { 0 },
-                    best_wpm: s.best_wpm,
-                });
-            }
-            Ok(())
-        }).await;
-        
-        // Sort leaderboard by games won
-        leaderboard.sort_by(|a, b| b.games_won.cmp(&a.games_won));
-        leaderboard.truncate(20);
-        
-        let total_games = *self.state.total_games.get();
-
+        // Resolvers read the views they need, so a query only pays for what it asks
         let schema = Schema::build(
-            QueryRoot { rooms, leaderboard, total_games },
+            QueryRoot {
+                state: self.state.clone(),
+                runtime: self.runtime.clone(),
+            },
             EmptyMutation,
             EmptySubscription,
         )
@@ -95,39 +64,472 @@ impl Service for TypeArenaService {
     }
 }
 
-/// GraphQL Query Root with owned data
+/// Page size of connections when `first` is not given
+const DEFAULT_PAGE_SIZE: usize = 10;
+
+/// GraphQL Query Root reading from the chain state on demand
 struct QueryRoot {
-    rooms: Vec<RoomInfo>,
-    leaderboard: Vec<LeaderboardEntry>,
-    total_games: u64,
+    state: Arc<TypeArenaState>,
+    runtime: Arc<ServiceRuntime<TypeArenaService>>,
 }
 
 #[Object]
 impl QueryRoot {
     /// Get a specific room by ID
-    async fn room(&self, room_id: String) -> Option<&RoomInfo> {
-        self.rooms.iter().find(|r| r.room_id == room_id)
+    async fn room(&self, room_id: String) -> Option<RoomInfo> {
+        let room_id: CryptoHash = room_id.parse().ok()?;
+        if let Some(room) = self.state.rooms.get(&room_id).await.expect("Failed to get room") {
+            return Some(self.room_info(room).await);
+        }
+        self.state.directory.get(&room_id).await
+            .expect("Failed to get listing")
+            .map(Self::listing_info)
     }
     
-    /// List all active (lobby) rooms that can be joined
-    async fn active_rooms(&self) -> Vec<&RoomInfo> {
-        self.rooms.iter().filter(|r| r.is_joinable).collect()
+    /// Active (lobby) rooms that can be joined, newest first, optionally only those
+    /// drawing prompts from one category. The directory (creator) chain answers
+    /// for rooms hosted on every chain.
+    async fn active_rooms(&self, category: Option<PromptCategory>, first: Option<i32>, after: Option<String>) -> async_graphql::Result<Connection<RoomOrder, RoomInfo>> {
+        query(after, None, first, None, |after: Option<RoomOrder>, _before, first, _last| async move {
+            let lobbies = RoomFilter {
+                status: Some(RoomStatus::Lobby),
+                ..RoomFilter::default()
+            };
+            let first = first.unwrap_or(DEFAULT_PAGE_SIZE);
+            Ok::<_, async_graphql::Error>(self.room_page(&lobbies, after, first, |info| {
+                info.is_joinable
+                    && info.visibility == RoomVisibility::Public
+                    && (category.is_none() || info.prompt_filter.category == category)
+            }).await)
+        }).await
     }
     
-    /// List all rooms
-    async fn all_rooms(&self) -> &[RoomInfo] {
-        &self.rooms
+    /// Rooms known to this chain matching `filter`, newest first
+    async fn rooms(&self, filter: Option<RoomFilter>, first: Option<i32>, after: Option<String>) -> async_graphql::Result<Connection<RoomOrder, RoomInfo>> {
+        query(after, None, first, None, |after: Option<RoomOrder>, _before, first, _last| async move {
+            let filter = filter.unwrap_or_default();
+            let first = first.unwrap_or(DEFAULT_PAGE_SIZE);
+            Ok::<_, async_graphql::Error>(self.room_page(&filter, after, first, |_| true).await)
+        }).await
     }
     
     /// Get global leaderboard
-    async fn leaderboard(&self, limit: Option<u32>) -> Vec<&LeaderboardEntry> {
-        let limit = limit.unwrap_or(10) as usize;
-        self.leaderboard.iter().take(limit).collect()
+    async fn leaderboard(&self, first: Option<i32>, after: Option<String>) -> async_graphql::Result<Connection<usize, LeaderboardEntry>> {
+        self.leaderboard_page(&self.state.players_by_wins, first, after).await
+    }
+    
+    /// Leaderboard ranked by skill rating instead of games won
+    async fn rating_leaderboard(&self, first: Option<i32>, after: Option<String>) -> async_graphql::Result<Connection<usize, LeaderboardEntry>> {
+        self.leaderboard_page(&self.state.players_by_rating, first, after).await
+    }
+    
+    /// A player's rating after each rated game, oldest first
+    async fn rating_history(&self, player: AccountOwner) -> Vec<RatingChange> {
+        match self.state.rating_history.try_load_entry(&player).await.expect("Failed to load rating history") {
+            Some(log) => log.read(..).await.expect("Failed to read rating history"),
+            None => Vec::new(),
+        }
+    }
+    
+    /// A player's finished games archived on this chain, newest first. Cursors are
+    /// positions in the player's history, so pages stay stable as games are added.
+    async fn match_history(&self, player: AccountOwner, first: Option<i32>, after: Option<String>) -> async_graphql::Result<Connection<usize, MatchRecord>> {
+        query(after, None, first, None, |after: Option<usize>, _before, first, _last| async move {
+            let Some(log) = self.state.player_matches.try_load_entry(&player).await.expect("Failed to load match index") else {
+                return Ok::<_, async_graphql::Error>(Connection::new(false, false));
+            };
+            let count = log.count();
+            let end = after.map_or(count, |after| after.min(count));
+            let start = end.saturating_sub(first.unwrap_or(DEFAULT_PAGE_SIZE));
+            let match_ids = log.read(start..end).await.expect("Failed to read match index");
+            
+            let mut connection = Connection::new(end < count, start > 0);
+            for (position, match_id) in (start..end).zip(match_ids).rev() {
+                if let Some(record) = self.state.matches.get(match_id as usize).await.expect("Failed to get match") {
+                    connection.edges.push(Edge::new(position, record));
+                }
+            }
+            Ok(connection)
+        }).await
+    }
+    
+    /// A single archived game
+    async fn match_record(&self, match_id: u64) -> Option<MatchRecord> {
+        self.state.matches.get(match_id as usize).await.expect("Failed to get match")
     }
     
     /// Get total games played
     async fn total_games(&self) -> u64 {
-        self.total_games
+        *self.state.total_games.get()
+    }
+    
+    /// Prompt library on this chain
+    async fn prompts(&self, include_retired: Option<bool>) -> Vec<Prompt> {
+        let include_retired = include_retired.unwrap_or(false);
+        let mut prompts = Vec::new();
+        self.state.prompts.for_each_index_value(|_id, prompt| {
+            if include_retired || !prompt.retired {
+                prompts.push(prompt.into_owned());
+            }
+            Ok(())
+        }).await.expect("Failed to read prompts");
+        prompts
+    }
+    
+    /// Compute the commitment to pass to `CreateRoom`/`JoinRoom` for a seed secret.
+    /// Only query your own node with this: the secret must stay private until revealed.
+    async fn seed_commitment(&self, owner: AccountOwner, secret: Vec<u8>) -> CryptoHash {
+        seed_commitment(owner, &secret)
+    }
+    
+    /// A player's profile, if this chain has it
+    async fn profile(&self, owner: AccountOwner) -> Option<Profile> {
+        self.state.profiles.get(&owner).await.expect("Failed to get profile")
+    }
+    
+    /// Who holds `name` or a look-alike of it (only populated on the registry chain)
+    async fn name_owner(&self, name: String) -> Option<RegisteredName> {
+        self.state.names.get(&normalize_name(&name)).await.expect("Failed to get name")
+    }
+    
+    /// The name the registry confirmed for `owner`, as known to this chain
+    async fn registered_name(&self, owner: AccountOwner) -> Option<String> {
+        self.state.registered_names.get(&owner).await.expect("Failed to get registered name")
+    }
+    
+    /// The last name claimed from this chain by `owner` and whether the registry accepted it
+    async fn name_claim(&self, owner: AccountOwner) -> Option<NameClaim> {
+        self.state.name_claims.get(&owner).await.expect("Failed to get claim")
+    }
+    
+    /// Compute the `passphrase_hash` to set when creating a private room
+    async fn passphrase_hash(&self, room_id: CryptoHash, passphrase: String) -> CryptoHash {
+        passphrase_hash(room_id, &passphrase)
+    }
+    
+    /// Rules this deployment was created with
+    async fn parameters(&self) -> TypeArenaParameters {
+        self.runtime.application_parameters()
+    }
+    
+    /// Join requests sent from this chain and whether the host accepted them
+    async fn pending_joins(&self) -> Vec<PendingJoinInfo> {
+        let mut pending_joins = Vec::new();
+        self.state.pending_joins.for_each_index_value(|_room_id, join| {
+            let j = join.into_owned();
+            pending_joins.push(PendingJoinInfo {
+                room_id: j.room_id.to_string(),
+                host_chain_id: j.host_chain_id.to_string(),
+                status: j.status,
+                reason: j.reason,
+            });
+            Ok(())
+        }).await.expect("Failed to read joins");
+        pending_joins
+    }
+    
+    /// Players waiting for a match (only populated on the matchmaker chain)
+    async fn match_queue(&self) -> Vec<QueueEntry> {
+        let mut match_queue = Vec::new();
+        self.state.match_queue.for_each_index_value(|_player, entry| {
+            match_queue.push(entry.into_owned());
+            Ok(())
+        }).await.expect("Failed to read queue");
+        match_queue
+    }
+    
+    /// Queue requests sent from this chain and the rooms they were matched into
+    async fn match_tickets(&self) -> Vec<MatchTicketInfo> {
+        let mut match_tickets = Vec::new();
+        self.state.match_tickets.for_each_index_value(|player, ticket| {
+            let t = ticket.into_owned();
+            match_tickets.push(MatchTicketInfo {
+                player: player.to_string(),
+                preferences: t.preferences,
+                room_id: t.room_id.map(|id| id.to_string()),
+                host_chain_id: t.host_chain_id.map(|id| id.to_string()),
+                reason: t.reason,
+            });
+            Ok(())
+        }).await.expect("Failed to read tickets");
+        match_tickets
+    }
+    
+    /// Last error a host chain sent back for each room
+    async fn rejections(&self) -> Vec<Rejection> {
+        let mut rejections = Vec::new();
+        self.state.rejections.for_each_index_value(|room_id, error| {
+            rejections.push(Rejection {
+                room_id: room_id.to_string(),
+                error: error.into_owned(),
+            });
+            Ok(())
+        }).await.expect("Failed to read rejections");
+        rejections
+    }
+}
+
+impl QueryRoot {
+    /// The owner's profile name, or `fallback` when this chain has no profile for them
+    async fn display_name(&self, owner: &AccountOwner, fallback: &str) -> String {
+        self.state.profiles.get(owner).await
+            .expect("Failed to get profile")
+            .map_or_else(|| fallback.to_string(), |profile| profile.display_name)
+    }
+    
+    /// Up to `first` rooms past `after` that match `filter` and `keep`, newest first. The
+    /// index is read in batches of one more than the page, stopping once the page is full
+    /// and one more room is found, so lookups do not load every room on the chain.
+    async fn room_page(&self, filter: &RoomFilter, after: Option<RoomOrder>, first: usize, keep: impl Fn(&RoomInfo) -> bool) -> Connection<RoomOrder, RoomInfo> {
+        let mut connection = Connection::new(after.is_some(), false);
+        let mut cursor = after;
+        loop {
+            let orders = self.room_orders(filter, cursor, first.saturating_add(1)).await;
+            let exhausted = orders.len() <= first;
+            for order in orders {
+                cursor = Some(order);
+                let Some(room) = self.stored_room(&order.room_id).await else {
+                    continue;
+                };
+                if !room.matches(filter) {
+                    continue;
+                }
+                let info = self.stored_room_info(room).await;
+                if !keep(&info) {
+                    continue;
+                }
+                if connection.edges.len() == first {
+                    connection.has_next_page = true;
+                    return connection;
+                }
+                connection.edges.push(Edge::new(order, info));
+            }
+            if exhausted {
+                return connection;
+            }
+        }
+    }
+    
+    /// Up to `limit` keys of `rooms_by_creation` past `after` that fall in the filter's
+    /// creation window, newest first. The index is read from the top, so every key
+    /// before the cursor is read again on each call.
+    async fn room_orders(&self, filter: &RoomFilter, after: Option<RoomOrder>, limit: usize) -> Vec<RoomOrder> {
+        let after = after.map(|after| after.to_custom_bytes().expect("Failed to encode cursor"));
+        let mut orders = Vec::new();
+        self.state.rooms_by_creation.for_each_index_while(|order| {
+            if filter.created_after.is_some_and(|created_after| order.created_at < created_after) {
+                return Ok(false);
+            }
+            let past_cursor = match &after {
+                Some(after) => order.to_custom_bytes()? > *after,
+                None => true,
+            };
+            if past_cursor && filter.matches_creation(order.created_at) {
+                orders.push(order);
+            }
+            Ok(orders.len() < limit)
+        }).await.expect("Failed to read room index");
+        orders
+    }
+    
+    /// The local copy of a room if this chain stores one, otherwise its directory listing
+    async fn stored_room(&self, room_id: &CryptoHash) -> Option<StoredRoom> {
+        if let Some(room) = self.state.rooms.get(room_id).await.expect("Failed to get room") {
+            return Some(StoredRoom::Local(Box::new(room)));
+        }
+        self.state.directory.get(room_id).await
+            .expect("Failed to get listing")
+            .map(StoredRoom::Listed)
+    }
+    
+    async fn stored_room_info(&self, room: StoredRoom) -> RoomInfo {
+        match room {
+            StoredRoom::Local(room) => self.room_info(*room).await,
+            StoredRoom::Listed(listing) => Self::listing_info(listing),
+        }
+    }
+    
+    async fn room_info(&self, r: GameRoom) -> RoomInfo {
+        // Chain state is public, so this only keeps honest clients from showing the prompt early;
+        // the contract enforces the countdown by checking keystroke timing against the reveal
+        let current_prompt = if r.has_round_started(self.runtime.system_time()) { r.current_prompt.clone() } else { None };
+        let mut players = Vec::new();
+        for p in &r.players {
+            players.push(PlayerInfo {
+                owner: p.owner.to_string(),
+                display_name: self.display_name(&p.owner, &p.display_name).await,
+                is_ready: p.is_ready,
+                seed_committed: p.seed_commitment.is_some(),
+                seed_revealed: p.seed_secret.is_some(),
+            });
+        }
+        RoomInfo {
+            room_id: r.room_id.to_string(),
+            host: r.host.to_string(),
+            host_chain_id: r.host_chain_id.to_string(),
+            player_count: r.players.len() as u32,
+            max_players: r.max_players,
+            status: format!("{:?}", r.status),
+            current_round: r.current_round,
+            total_rounds: r.total_rounds,
+            round_time_limit_secs: r.round_time_limit_secs,
+            prompt_filter: r.prompt_filter.clone(),
+            visibility: r.access.visibility,
+            auto_start: r.auto_start,
+            round_start_time: r.round_start_time,
+            current_prompt,
+            players,
+            is_joinable: r.status == RoomStatus::Lobby && !r.roster_locked && !r.is_full(),
+            roster_locked: r.roster_locked,
+            bans: r.bans,
+            round_history: r.round_history,
+            created_at: r.created_at,
+            last_activity: r.last_activity,
+        }
+    }
+    
+    fn listing_info(l: RoomListing) -> RoomInfo {
+        RoomInfo {
+            room_id: l.room_id.to_string(),
+            host: l.host.to_string(),
+            host_chain_id: l.host_chain_id.to_string(),
+            player_count: l.player_count as u32,
+            max_players: l.max_players,
+            status: format!("{:?}", RoomStatus::Lobby),
+            current_round: 0,
+            total_rounds: l.total_rounds,
+            round_time_limit_secs: l.round_time_limit_secs,
+            prompt_filter: l.prompt_filter,
+            visibility: RoomVisibility::Public,
+            auto_start: l.auto_start,
+            round_start_time: None,
+            current_prompt: None,
+            players: Vec::new(),
+            is_joinable: l.player_count < l.max_players,
+            roster_locked: false,
+            bans: Vec::new(),
+            round_history: Vec::new(),
+            created_at: l.created_at,
+            last_activity: l.updated_at,
+        }
+    }
+    
+    /// One page of a leaderboard index, with stats and names resolved. Cursors are ranks,
+    /// counted from zero; only the keys up to the end of the page are read.
+    async fn leaderboard_page(&self, ranking: &CustomSetView<PlayerRank>, first: Option<i32>, after: Option<String>) -> async_graphql::Result<Connection<usize, LeaderboardEntry>> {
+        query(after, None, first, None, |after: Option<usize>, _before, first, _last| async move {
+            let start = after.map_or(0, |after| after.saturating_add(1));
+            let end = start.saturating_add(first.unwrap_or(DEFAULT_PAGE_SIZE));
+            let mut players = Vec::new();
+            let mut position = 0;
+            ranking.for_each_index_while(|rank| {
+                if position >= start {
+                    players.push(rank.player);
+                }
+                position += 1;
+                Ok(position <= end)
+            }).await.expect("Failed to read leaderboard");
+            
+            let has_next = players.len() > end - start;
+            players.truncate(end - start);
+            let mut connection = Connection::new(start > 0, has_next);
+            for (index, owner) in (start..).zip(players) {
+                let stats = self.state.player_stats.get(&owner).await
+                    .expect("Failed to get stats")
+                    .unwrap_or_default();
+                connection.edges.push(Edge::new(index, self.leaderboard_entry(index, owner, stats).await));
+            }
+            Ok::<_, async_graphql::Error>(connection)
+        }).await
+    }
+    
+    async fn leaderboard_entry(&self, index: usize, owner: AccountOwner, s: PlayerStats) -> LeaderboardEntry {
+        LeaderboardEntry {
+            rank: index as u32 + 1,
+            player: owner.to_string(),
+            display_name: self.display_name(&owner, &Profile::default_for(&owner).display_name).await,
+            games_won: s.games_won,
+            games_played: s.games_played,
+            average_wpm: s.average_wpm(),
+            average_accuracy: s.average_accuracy(),
+            best_wpm: s.best_wpm,
+            rating: s.rating.rating,
+            rating_deviation: s.rating.deviation,
+        }
+    }
+}
+
+/// A room as stored on this chain, or only as a directory listing
+enum StoredRoom {
+    Local(Box<GameRoom>),
+    Listed(RoomListing),
+}
+
+impl StoredRoom {
+    fn matches(&self, filter: &RoomFilter) -> bool {
+        match self {
+            StoredRoom::Local(room) => filter.matches_room(room),
+            StoredRoom::Listed(listing) => filter.matches_listing(listing),
+        }
+    }
+}
+
+/// Room cursors are positions in `rooms_by_creation`, encoded as `<created_at micros>:<room_id>`
+impl CursorType for RoomOrder {
+    type Error = InvalidCursor;
+    
+    fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+        let (created_at, room_id) = s.split_once(':').ok_or(InvalidCursor)?;
+        Ok(RoomOrder {
+            created_at: Timestamp::from(created_at.parse::<u64>().map_err(|_| InvalidCursor)?),
+            room_id: room_id.parse().map_err(|_| InvalidCursor)?,
+        })
+    }
+    
+    fn encode_cursor(&self) -> String {
+        format!("{}:{}", self.created_at.micros(), self.room_id)
+    }
+}
+
+pub struct InvalidCursor;
+
+impl fmt::Display for InvalidCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid room cursor")
+    }
+}
+
+/// Which rooms the `rooms` query returns; every field set must match
+#[derive(Default, InputObject)]
+struct RoomFilter {
+    status: Option<RoomStatus>,
+    host: Option<AccountOwner>,
+    /// Directory entries only know their host, so they match only the host here
+    player: Option<AccountOwner>,
+    /// Rooms created at or after this time
+    created_after: Option<Timestamp>,
+    /// Rooms created before this time
+    created_before: Option<Timestamp>,
+}
+
+impl RoomFilter {
+    fn matches_room(&self, room: &GameRoom) -> bool {
+        self.status.is_none_or(|status| room.status == status)
+            && self.host.is_none_or(|host| room.host == host)
+            && self.player.is_none_or(|player| room.has_player(&player))
+            && self.matches_creation(room.created_at)
+    }
+    
+    fn matches_listing(&self, listing: &RoomListing) -> bool {
+        self.status.is_none_or(|status| status == RoomStatus::Lobby)
+            && self.host.is_none_or(|host| listing.host == host)
+            && self.player.is_none_or(|player| listing.host == player)
+            && self.matches_creation(listing.created_at)
+    }
+    
+    fn matches_creation(&self, created_at: Timestamp) -> bool {
+        self.created_after.is_none_or(|after| created_at >= after)
+            && self.created_before.is_none_or(|before| created_at < before)
     }
 }
 
@@ -136,14 +538,33 @@ impl QueryRoot {
 struct RoomInfo {
     room_id: String,
     host: String,
+    host_chain_id: String,
     player_count: u32,
     max_players: u8,
     status: String,
     current_round: u8,
     total_rounds: u8,
+    round_time_limit_secs: u32,
+    prompt_filter: PromptFilter,
+    visibility: RoomVisibility,
+    /// Starts by itself once every player is ready and has revealed their seed
+    auto_start: bool,
+    /// When the current round's prompt is revealed
+    round_start_time: Option<Timestamp>,
+    /// Hidden until `round_start_time`
     current_prompt: Option<String>,
+    /// Empty for directory entries; query the host chain for details
     players: Vec<PlayerInfo>,
     is_joinable: bool,
+    /// Every player is ready: seeds are being revealed and nobody can join or be kicked
+    roster_locked: bool,
+    /// Players the host kicked or banned
+    bans: Vec<RoomBan>,
+    /// Results of each finished round, for the round-by-round breakdown
+    round_history: Vec<RoundRecord>,
+    created_at: Timestamp,
+    /// Rooms idle for `parameters.roomIdleExpirySecs` are deleted by `CleanupRooms`
+    last_activity: Timestamp,
 }
 
 /// Player info for GraphQL
@@ -152,15 +573,49 @@ struct PlayerInfo {
     owner: String,
     display_name: String,
     is_ready: bool,
+    /// False after a reopened lobby discarded the player's revealed secret
+    seed_committed: bool,
+    seed_revealed: bool,
+}
+
+/// Join request status for GraphQL
+#[derive(SimpleObject)]
+struct PendingJoinInfo {
+    room_id: String,
+    host_chain_id: String,
+    status: JoinStatus,
+    reason: Option<TypeArenaError>,
+}
+
+/// Matchmaking ticket for GraphQL
+#[derive(SimpleObject)]
+struct MatchTicketInfo {
+    player: String,
+    preferences: MatchPreferences,
+    room_id: Option<String>,
+    host_chain_id: Option<String>,
+    reason: Option<TypeArenaError>,
+}
+
+/// Rejected request for GraphQL
+#[derive(SimpleObject)]
+struct Rejection {
+    room_id: String,
+    error: TypeArenaError,
 }
 
 /// Leaderboard entry for GraphQL
 #[derive(SimpleObject)]
 struct LeaderboardEntry {
+    /// Position in this leaderboard, starting at 1
+    rank: u32,
     player: String,
+    display_name: String,
     games_won: u32,
     games_played: u32,
     average_wpm: u32,
     average_accuracy: u32,
     best_wpm: u32,
+    rating: f64,
+    rating_deviation: f64,
 }
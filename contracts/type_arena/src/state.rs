@@ -1,9 +1,16 @@
 use linera_sdk::{
-    linera_base_types::{AccountOwner, ChainId, CryptoHash, Timestamp},
-    views::{MapView, RegisterView, RootView, ViewStorageContext},
+    linera_base_types::{AccountOwner, ChainId, CryptoHash, TimeDelta, Timestamp},
+    views::{
+        CollectionView, CustomSerialize, CustomSetView, LogView, MapView, RegisterView, RootView,
+        SetView, ViewError, ViewStorageContext,
+    },
 };
 use serde::{Deserialize, Serialize};
-use type_arena::{PlayerResult, RoomStatus};
+use type_arena::{
+    seed_commitment, JoinStatus, MatchPreferences, PlayerResult, Profile, Prompt, PromptFilter, Rating,
+    RoomAccess, RoomListing, RoomStatus, TypeArenaError, HOST_TIMEOUT_SECS, MAX_SEED_SECRET_BYTES,
+    MIN_SEED_SECRET_BYTES,
+};
 
 /// Root state for Type Arena application
 #[derive(RootView)]
@@ -11,10 +18,58 @@ use type_arena::{PlayerResult, RoomStatus};
 pub struct TypeArenaState {
     /// All game rooms indexed by room_id
     pub rooms: MapView<CryptoHash, GameRoom>,
+    /// Local rooms and directory listings, newest first
+    pub rooms_by_creation: CustomSetView<RoomOrder>,
     /// Player statistics indexed by AccountOwner
     pub player_stats: MapView<AccountOwner, PlayerStats>,
+    /// Players with a finished game, most games won first
+    pub players_by_wins: CustomSetView<PlayerRank>,
+    /// Players with a finished game, highest rating first
+    pub players_by_rating: CustomSetView<PlayerRank>,
+    /// Rating after each rated game, oldest first
+    pub rating_history: CollectionView<AccountOwner, LogView<RatingChange>>,
+    /// Finished games, in the order they ended; a record's position is its match id
+    pub matches: LogView<MatchRecord>,
+    /// Ids in `matches` of the games each player took part in, oldest first
+    pub player_matches: CollectionView<AccountOwner, LogView<u64>>,
     /// Total games played counter
     pub total_games: RegisterView<u64>,
+    /// Owners allowed to manage the prompt library (creator chain only)
+    pub admins: SetView<AccountOwner>,
+    /// Prompt library indexed by prompt id. Managed on the creator chain and
+    /// replicated to subscribed chains.
+    pub prompts: MapView<u32, Prompt>,
+    /// Next prompt id to assign (creator chain only)
+    pub next_prompt_id: RegisterView<u32>,
+    /// Chains that receive prompt library updates (creator chain only)
+    pub prompt_subscribers: SetView<ChainId>,
+    /// Whether this chain has asked the creator chain for the library
+    pub prompts_subscribed: RegisterView<bool>,
+    /// Last rejection received from a host chain, per room
+    pub rejections: MapView<CryptoHash, TypeArenaError>,
+    /// Join requests sent from this chain and their outcome
+    pub pending_joins: MapView<CryptoHash, PendingJoin>,
+    /// Players waiting for a match (matchmaker chain only)
+    pub match_queue: MapView<AccountOwner, QueueEntry>,
+    /// Counter used to derive matchmade room ids (matchmaker chain only)
+    pub next_match_id: RegisterView<u64>,
+    /// Player chains of matchmade rooms that have not reported their result yet
+    /// (matchmaker chain only)
+    pub open_matches: MapView<CryptoHash, Vec<ChainId>>,
+    /// Queue requests sent from this chain and the room they were matched into
+    pub match_tickets: MapView<AccountOwner, MatchTicket>,
+    /// Joinable rooms across the application (directory chain only)
+    pub directory: MapView<CryptoHash, RoomListing>,
+    /// Profiles of this chain's players, and the latest copies received from others
+    pub profiles: MapView<AccountOwner, Profile>,
+    /// Registered display names keyed by `normalize_name` (registry chain only)
+    pub names: MapView<String, RegisteredName>,
+    /// Normalized name held by each owner (registry chain only)
+    pub names_by_owner: MapView<AccountOwner, String>,
+    /// Latest name claim made from this chain by each owner and its outcome
+    pub name_claims: MapView<AccountOwner, NameClaim>,
+    /// Names the registry confirmed for this chain's players
+    pub registered_names: MapView<AccountOwner, String>,
 }
 
 /// A game room with all its state
@@ -23,6 +78,10 @@ pub struct GameRoom {
     pub room_id: CryptoHash,
     pub host: AccountOwner,
     pub host_chain_id: ChainId,
+    /// Incremented on every host handover
+    pub host_epoch: u32,
+    /// Last time this chain heard from (or acted as) the host
+    pub last_host_activity: Timestamp,
     pub players: Vec<PlayerInfo>,
     pub status: RoomStatus,
     pub max_players: u8,
@@ -30,32 +89,65 @@ pub struct GameRoom {
     pub current_round: u8,
     pub current_prompt: Option<String>,
     pub round_start_time: Option<Timestamp>,
+    pub round_time_limit_secs: u32,
+    /// Which library prompts this room draws from
+    pub prompt_filter: PromptFilter,
+    /// Combined player seed secrets; fixed at `StartGame`, drives prompt selection
+    pub prompt_seed: Option<CryptoHash>,
+    /// Running hash of the keystroke logs submitted this game. Each draw mixes it in, so
+    /// a round's prompt cannot be worked out before the previous round's logs are in.
+    pub round_entropy: Option<CryptoHash>,
+    /// Formed by the matchmaker, which rates the result
+    pub matchmade: bool,
+    /// Visibility and who may join
+    pub access: RoomAccess,
+    /// Start without `StartGame` once everyone is ready and revealed
+    pub auto_start: bool,
+    /// Set in the lobby once every player is ready. Seeds are only revealed while the
+    /// roster is locked, and joins, kicks and new commitments are refused.
+    pub roster_locked: bool,
+    /// Players the host removed and may not rejoin
+    pub bans: Vec<RoomBan>,
+    /// Library ids of the prompts drawn this game, in round order
+    pub used_prompt_ids: Vec<u32>,
     pub round_results: Vec<PlayerResult>,
+    /// Full results of every finished round, oldest first
+    pub round_history: Vec<RoundRecord>,
     pub game_scores: Vec<PlayerScore>,
     pub created_at: Timestamp,
+    /// Last time this chain stored a change to the room
+    pub last_activity: Timestamp,
 }
 
 impl GameRoom {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         room_id: CryptoHash,
         host: AccountOwner,
         host_chain_id: ChainId,
         host_name: String,
+        host_seed_commitment: CryptoHash,
         max_players: u8,
         rounds: u8,
+        round_time_limit_secs: u32,
+        prompt_filter: PromptFilter,
         created_at: Timestamp,
     ) -> Self {
         let host_info = PlayerInfo {
             owner: host,
             chain_id: host_chain_id,
             display_name: host_name,
-            is_ready: true,
+            is_ready: false,
+            seed_commitment: Some(host_seed_commitment),
+            seed_secret: None,
         };
         
         Self {
             room_id,
             host,
             host_chain_id,
+            host_epoch: 0,
+            last_host_activity: created_at,
             players: vec![host_info],
             status: RoomStatus::Lobby,
             max_players,
@@ -63,16 +155,105 @@ impl GameRoom {
             current_round: 0,
             current_prompt: None,
             round_start_time: None,
+            round_time_limit_secs,
+            prompt_filter,
+            prompt_seed: None,
+            round_entropy: None,
+            matchmade: false,
+            access: RoomAccess::default(),
+            auto_start: false,
+            roster_locked: false,
+            bans: Vec::new(),
+            used_prompt_ids: Vec::new(),
             round_results: Vec::new(),
+            round_history: Vec::new(),
             game_scores: vec![PlayerScore {
                 player: host,
                 total_score: 0,
                 rounds_completed: 0,
+                forfeited: false,
             }],
             created_at,
+            last_activity: created_at,
+        }
+    }
+    
+    /// Seat a player who has not revealed their seed yet
+    pub fn add_player(&mut self, owner: AccountOwner, chain_id: ChainId, display_name: String, seed_commitment: CryptoHash) {
+        self.players.push(PlayerInfo {
+            owner,
+            chain_id,
+            display_name,
+            is_ready: false,
+            seed_commitment: Some(seed_commitment),
+            seed_secret: None,
+        });
+        self.game_scores.push(PlayerScore {
+            player: owner,
+            total_score: 0,
+            rounds_completed: 0,
+            forfeited: false,
+        });
+    }
+    
+    /// Whether the room belongs in the directory: open public lobbies
+    pub fn is_listed(&self) -> bool {
+        self.status == RoomStatus::Lobby
+            && !self.roster_locked
+            && !self.players.is_empty()
+            && !self.matchmade
+            && !self.access.is_private()
+    }
+    
+    /// Directory entry for this room as of `now`
+    pub fn listing(&self, now: Timestamp) -> RoomListing {
+        RoomListing {
+            room_id: self.room_id,
+            host: self.host,
+            host_chain_id: self.host_chain_id,
+            host_epoch: self.host_epoch,
+            player_count: self.players.len() as u8,
+            max_players: self.max_players,
+            total_rounds: self.total_rounds,
+            round_time_limit_secs: self.round_time_limit_secs,
+            prompt_filter: self.prompt_filter.clone(),
+            auto_start: self.auto_start,
+            created_at: self.created_at,
+            updated_at: now,
         }
     }
     
+    /// Drop a player along with their scores. A lobby roster change reopens the lobby.
+    pub fn remove_player(&mut self, owner: &AccountOwner) {
+        self.players.retain(|p| &p.owner != owner);
+        self.game_scores.retain(|s| &s.player != owner);
+        self.round_results.retain(|r| &r.player != owner);
+        if self.status == RoomStatus::Lobby {
+            self.unlock_roster();
+        }
+    }
+    
+    /// Take a player who left mid-game off the roster. Their score stays in the
+    /// standings as a forfeit, so leaving cannot dodge a rating loss.
+    pub fn forfeit(&mut self, owner: &AccountOwner) {
+        self.players.retain(|p| &p.owner != owner);
+        self.round_results.retain(|r| &r.player != owner);
+        if let Some(score) = self.game_scores.iter_mut().find(|s| &s.player == owner) {
+            score.forfeited = true;
+        }
+    }
+    
+    /// Keep `owner` out until `until`, or for good if unset
+    pub fn ban(&mut self, owner: AccountOwner, until: Option<Timestamp>) {
+        self.bans.retain(|ban| ban.player != owner);
+        self.bans.push(RoomBan { player: owner, until });
+    }
+    
+    pub fn is_banned(&self, owner: &AccountOwner, now: Timestamp) -> bool {
+        self.bans.iter()
+            .any(|ban| &ban.player == owner && ban.until.is_none_or(|until| now < until))
+    }
+    
     pub fn is_full(&self) -> bool {
         self.players.len() >= self.max_players as usize
     }
@@ -85,9 +266,249 @@ impl GameRoom {
         self.players.iter().find(|p| &p.owner == owner).map(|p| p.chain_id)
     }
     
+    /// Whether a game in progress has heard nothing from its host for `HOST_TIMEOUT_SECS`.
+    /// Hosts send nothing while a round is being typed, so silence only counts from the
+    /// round's deadline. A quiet lobby is not a dead host, so lobbies never time out.
+    pub fn host_timed_out(&self, now: Timestamp) -> bool {
+        if self.status != RoomStatus::InProgress {
+            return false;
+        }
+        let silent_since = self.round_deadline()
+            .map_or(self.last_host_activity, |deadline| deadline.max(self.last_host_activity));
+        now > silent_since.saturating_add(TimeDelta::from_secs(HOST_TIMEOUT_SECS))
+    }
+    
+    /// Whether the room has sat idle for `idle_secs`, or stayed in the lobby for
+    /// `lobby_max_age_secs` since it was created
+    pub fn is_expired(&self, now: Timestamp, idle_secs: u64, lobby_max_age_secs: u64) -> bool {
+        now > self.last_activity.saturating_add(TimeDelta::from_secs(idle_secs))
+            || (self.status == RoomStatus::Lobby
+                && now > self.created_at.saturating_add(TimeDelta::from_secs(lobby_max_age_secs)))
+    }
+    
+    /// Whether a host announcement should replace the current host. Later epochs win;
+    /// competing claims in the same epoch go to the player who joined first.
+    pub fn accepts_host(&self, host: &AccountOwner, host_epoch: u32) -> bool {
+        if host_epoch != self.host_epoch {
+            return host_epoch > self.host_epoch;
+        }
+        let position = |owner: &AccountOwner| self.players.iter().position(|p| &p.owner == owner);
+        match (position(host), position(&self.host)) {
+            (Some(new), Some(current)) => new <= current,
+            (Some(_), None) => true,
+            _ => false,
+        }
+    }
+    
+    /// Replace the player's commitment while the roster is open
+    pub fn commit_seed(&mut self, player: &AccountOwner, commitment: CryptoHash) -> Result<(), TypeArenaError> {
+        if self.roster_locked {
+            return Err(TypeArenaError::RosterLocked);
+        }
+        let info = self.players.iter_mut()
+            .find(|p| &p.owner == player)
+            .ok_or(TypeArenaError::NotInRoom)?;
+        info.seed_commitment = Some(commitment);
+        info.seed_secret = None;
+        Ok(())
+    }
+    
+    /// Check `secret` against the player's commitment and record it. Secrets are
+    /// only accepted once the roster is locked, so nobody can commit after seeing one.
+    pub fn reveal_seed(&mut self, player: &AccountOwner, secret: Vec<u8>) -> Result<(), TypeArenaError> {
+        if !(MIN_SEED_SECRET_BYTES..=MAX_SEED_SECRET_BYTES).contains(&secret.len()) {
+            return Err(TypeArenaError::InvalidSeedSecret);
+        }
+        if !self.roster_locked {
+            return Err(TypeArenaError::RosterNotLocked);
+        }
+        let info = self.players.iter_mut()
+            .find(|p| &p.owner == player)
+            .ok_or(TypeArenaError::NotInRoom)?;
+        let commitment = info.seed_commitment.ok_or(TypeArenaError::SeedNotCommitted)?;
+        if seed_commitment(*player, &secret) != commitment {
+            return Err(TypeArenaError::SeedMismatch);
+        }
+        info.seed_secret = Some(secret);
+        Ok(())
+    }
+    
+    /// Mark a player ready or not. The roster locks once at least two players are
+    /// all ready, and withdrawing a ready mark reopens it.
+    pub fn set_ready(&mut self, player: &AccountOwner, ready: bool) -> Result<(), TypeArenaError> {
+        let info = self.players.iter_mut()
+            .find(|p| &p.owner == player)
+            .ok_or(TypeArenaError::NotInRoom)?;
+        if ready && info.seed_commitment.is_none() {
+            return Err(TypeArenaError::SeedNotCommitted);
+        }
+        info.is_ready = ready;
+        
+        if !ready {
+            self.unlock_roster();
+        } else if self.players.len() >= 2 && self.all_ready() {
+            self.roster_locked = true;
+        }
+        Ok(())
+    }
+    
+    /// Reopen a locked lobby. Revealed secrets are public now, so their owners must
+    /// commit to fresh ones, and everyone marks ready again to confirm the new roster.
+    pub fn unlock_roster(&mut self) {
+        if !self.roster_locked {
+            return;
+        }
+        self.roster_locked = false;
+        for info in &mut self.players {
+            if info.seed_secret.take().is_some() {
+                info.seed_commitment = None;
+            }
+            info.is_ready = false;
+        }
+    }
+    
+    pub fn all_ready(&self) -> bool {
+        self.players.iter().all(|p| p.is_ready)
+    }
+    
+    pub fn all_seeds_revealed(&self) -> bool {
+        self.players.iter().all(|p| p.seed_secret.is_some())
+    }
+    
     pub fn all_results_submitted(&self) -> bool {
         self.round_results.len() >= self.players.len()
     }
+    
+    /// Time after which results for the current round are rejected
+    pub fn round_deadline(&self) -> Option<Timestamp> {
+        self.round_start_time
+            .map(|start| start.saturating_add(TimeDelta::from_secs(self.round_time_limit_secs as u64)))
+    }
+    
+    pub fn is_round_expired(&self, now: Timestamp) -> bool {
+        self.round_deadline().is_some_and(|deadline| now > deadline)
+    }
+    
+    /// Whether the countdown is over and the prompt may be shown
+    pub fn has_round_started(&self, now: Timestamp) -> bool {
+        self.round_start_time.is_some_and(|start| now >= start)
+    }
+    
+    /// Milliseconds since the prompt was revealed
+    pub fn round_elapsed_ms(&self, now: Timestamp) -> u64 {
+        self.round_start_time
+            .map_or(0, |start| now.delta_since(start).as_micros() / 1000)
+    }
+    
+    /// Archive a finished round's results under the current prompt, once
+    pub fn record_round(&mut self, round: u8, results: Vec<PlayerResult>, ended_at: Timestamp) {
+        if self.round_history.iter().any(|r| r.round == round) {
+            return;
+        }
+        self.round_history.push(RoundRecord {
+            round,
+            prompt: self.current_prompt.clone().unwrap_or_default(),
+            results,
+            start_time: self.round_start_time,
+            ended_at,
+        });
+    }
+    
+    /// Record a DNF result for every player who has not submitted this round
+    pub fn mark_missing_as_dnf(&mut self) {
+        let missing: Vec<PlayerResult> = self.players.iter()
+            .filter(|p| !self.round_results.iter().any(|r| r.player == p.owner))
+            .map(|p| PlayerResult {
+                player: p.owner,
+                player_name: p.display_name.clone(),
+                wpm: 0,
+                accuracy: 0,
+                time_ms: 0,
+                score: 0,
+                dnf: true,
+            })
+            .collect();
+        self.round_results.extend(missing);
+    }
+}
+
+/// Results of one finished round
+#[derive(Debug, Deserialize, Serialize, Clone, async_graphql::SimpleObject)]
+pub struct RoundRecord {
+    pub round: u8,
+    pub prompt: String,
+    pub results: Vec<PlayerResult>,
+    pub start_time: Option<Timestamp>,
+    pub ended_at: Timestamp,
+}
+
+/// An archived finished game; never modified once written
+#[derive(Debug, Deserialize, Serialize, Clone, async_graphql::SimpleObject)]
+pub struct MatchRecord {
+    pub match_id: u64,
+    pub room_id: CryptoHash,
+    /// Host when the game ended
+    pub host: AccountOwner,
+    pub participants: Vec<AccountOwner>,
+    /// Total scores, in the host's player order
+    pub final_standings: Vec<PlayerResult>,
+    /// Prompt and results of every round
+    pub rounds: Vec<RoundRecord>,
+    pub winner: AccountOwner,
+    pub matchmade: bool,
+    pub created_at: Timestamp,
+    /// When the first round's prompt was revealed
+    pub started_at: Option<Timestamp>,
+    pub ended_at: Timestamp,
+}
+
+/// A join request sent from this chain to a host chain
+#[derive(Debug, Deserialize, Serialize, Clone, async_graphql::SimpleObject)]
+pub struct PendingJoin {
+    pub room_id: CryptoHash,
+    pub host_chain_id: ChainId,
+    pub status: JoinStatus,
+    pub reason: Option<TypeArenaError>,
+    pub requested_at: Timestamp,
+}
+
+/// A player waiting on the matchmaker chain
+#[derive(Debug, Deserialize, Serialize, Clone, async_graphql::SimpleObject)]
+pub struct QueueEntry {
+    pub player: AccountOwner,
+    pub player_chain_id: ChainId,
+    pub profile: Profile,
+    pub seed_commitment: CryptoHash,
+    pub preferences: MatchPreferences,
+    /// Matchmaker's rating of the player when they joined the queue
+    pub rating: f64,
+    pub enqueued_at: Timestamp,
+}
+
+/// A queue request sent from this chain to the matchmaker
+#[derive(Debug, Deserialize, Serialize, Clone, async_graphql::SimpleObject)]
+pub struct MatchTicket {
+    pub preferences: MatchPreferences,
+    /// Set once the matchmaker has formed a room
+    pub room_id: Option<CryptoHash>,
+    pub host_chain_id: Option<ChainId>,
+    pub reason: Option<TypeArenaError>,
+    pub requested_at: Timestamp,
+}
+
+impl MatchTicket {
+    /// Still waiting on the matchmaker: neither matched nor rejected
+    pub fn is_pending(&self) -> bool {
+        self.room_id.is_none() && self.reason.is_none()
+    }
+}
+
+/// A player the host removed from a room
+#[derive(Debug, Deserialize, Serialize, Clone, async_graphql::SimpleObject)]
+pub struct RoomBan {
+    pub player: AccountOwner,
+    /// End of a kick's cooldown; unset for a ban
+    pub until: Option<Timestamp>,
 }
 
 /// Player information in a room
@@ -97,6 +518,11 @@ pub struct PlayerInfo {
     pub chain_id: ChainId,
     pub display_name: String,
     pub is_ready: bool,
+    /// Unset once a reopened lobby discarded the revealed secret, until the player
+    /// commits to a fresh one
+    pub seed_commitment: Option<CryptoHash>,
+    /// Revealed secret behind `seed_commitment`
+    pub seed_secret: Option<Vec<u8>>,
 }
 
 /// Player score tracking across rounds
@@ -105,6 +531,8 @@ pub struct PlayerScore {
     pub player: AccountOwner,
     pub total_score: u32,
     pub rounds_completed: u8,
+    /// Left the game before it ended
+    pub forfeited: bool,
 }
 
 /// Persistent player statistics
@@ -117,6 +545,7 @@ pub struct PlayerStats {
     pub rounds_played: u32,
     pub best_wpm: u32,
     pub best_accuracy: u32,
+    pub rating: Rating,
 }
 
 impl PlayerStats {
@@ -149,3 +578,100 @@ impl PlayerStats {
         }
     }
 }
+
+/// A display name held on the registry chain
+#[derive(Debug, Deserialize, Serialize, Clone, async_graphql::SimpleObject)]
+pub struct RegisteredName {
+    /// The name as claimed, before normalization
+    pub name: String,
+    pub owner: AccountOwner,
+    /// Chain notified when the name changes hands
+    pub owner_chain_id: ChainId,
+    pub registered_at: Timestamp,
+}
+
+/// A name claim sent from this chain to the registry
+#[derive(Debug, Deserialize, Serialize, Clone, async_graphql::SimpleObject)]
+pub struct NameClaim {
+    pub name: String,
+    pub status: JoinStatus,
+    pub reason: Option<TypeArenaError>,
+    pub requested_at: Timestamp,
+}
+
+/// Position of a room in `rooms_by_creation`. Keys are stored newest first, with ties
+/// broken by room id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoomOrder {
+    pub created_at: Timestamp,
+    pub room_id: CryptoHash,
+}
+
+impl CustomSerialize for RoomOrder {
+    fn to_custom_bytes(&self) -> Result<Vec<u8>, ViewError> {
+        let mut bytes = (u64::MAX - self.created_at.micros()).to_be_bytes().to_vec();
+        bytes.extend(bcs::to_bytes(&self.room_id)?);
+        Ok(bytes)
+    }
+    
+    fn from_custom_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
+        let (newest_first, room_id) = split_order_prefix(bytes)?;
+        Ok(RoomOrder {
+            created_at: Timestamp::from(u64::MAX - newest_first),
+            room_id: bcs::from_bytes(room_id)?,
+        })
+    }
+}
+
+/// Position of a player in a leaderboard index: highest `score` first, ties broken by owner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerRank {
+    pub score: u64,
+    pub player: AccountOwner,
+}
+
+impl PlayerRank {
+    pub fn by_wins(player: AccountOwner, stats: &PlayerStats) -> Self {
+        PlayerRank { score: stats.games_won as u64, player }
+    }
+    
+    /// Maps the rating onto a `u64` that sorts the same way as the float
+    pub fn by_rating(player: AccountOwner, stats: &PlayerStats) -> Self {
+        let bits = stats.rating.rating.to_bits();
+        let score = if bits >> 63 == 1 { !bits } else { bits | 1 << 63 };
+        PlayerRank { score, player }
+    }
+}
+
+impl CustomSerialize for PlayerRank {
+    fn to_custom_bytes(&self) -> Result<Vec<u8>, ViewError> {
+        let mut bytes = (u64::MAX - self.score).to_be_bytes().to_vec();
+        bytes.extend(bcs::to_bytes(&self.player)?);
+        Ok(bytes)
+    }
+    
+    fn from_custom_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
+        let (inverted_score, player) = split_order_prefix(bytes)?;
+        Ok(PlayerRank {
+            score: u64::MAX - inverted_score,
+            player: bcs::from_bytes(player)?,
+        })
+    }
+}
+
+/// Split an index key into its big-endian ordering prefix and the rest
+fn split_order_prefix(bytes: &[u8]) -> Result<(u64, &[u8]), ViewError> {
+    let (prefix, rest) = bytes.split_first_chunk::<8>()
+        .ok_or(bcs::Error::Eof)?;
+    Ok((u64::from_be_bytes(*prefix), rest))
+}
+
+/// A player's rating after a finished game
+#[derive(Debug, Deserialize, Serialize, Clone, async_graphql::SimpleObject)]
+pub struct RatingChange {
+    pub room_id: CryptoHash,
+    pub rating: Rating,
+    /// Change in `rating.rating` caused by this game
+    pub delta: f64,
+    pub timestamp: Timestamp,
+}
@@ -33,21 +33,23 @@ export default function LeaderboardPage() {
         try {
             const result = await queryState(`
         query {
-          leaderboard(limit: 20) {
-            player
-            gamesWon
-            gamesPlayed
-            averageWpm
-            averageAccuracy
-            bestWpm
+          leaderboard(first: 20) {
+            edges {
+              node {
+                rank
+                player
+                gamesWon
+                gamesPlayed
+                averageWpm
+                averageAccuracy
+                bestWpm
+              }
+            }
           }
         }
       `);
             if (result?.leaderboard) {
-                setEntries(result.leaderboard.map((e: any, i: number) => ({
-                    rank: i + 1,
-                    ...e,
-                })));
+                setEntries(result.leaderboard.edges.map(({ node }: any) => node));
             }
         } catch (err) {
             console.error('Failed to fetch leaderboard:', err);
@@ -43,18 +43,22 @@ export default function LobbyPage() {
         try {
             const result = await queryState(`
         query {
-          activeRooms {
-            roomId
-            host
-            playerCount
-            maxPlayers
-            status
-            totalRounds
+          activeRooms(first: 20) {
+            edges {
+              node {
+                roomId
+                host
+                playerCount
+                maxPlayers
+                status
+                totalRounds
+              }
+            }
           }
         }
       `);
             if (result?.activeRooms) {
-                setRooms(result.activeRooms.map((r: any) => ({
+                setRooms(result.activeRooms.edges.map(({ node: r }: any) => ({
                     id: r.roomId,
                     host: r.host,
                     playerCount: r.playerCount,